@@ -0,0 +1,250 @@
+// -----------------------------------------------------------------------------
+//     - App access tokens -
+//     https://dev.twitch.tv/docs/authentication/getting-tokens-oauth/#client-credentials-grant-flow
+//
+//     With a client secret we can mint our own tokens instead of having the
+//     user paste one in. They're cached on disk so every run doesn't cost a
+//     round trip to id.twitch.tv.
+// -----------------------------------------------------------------------------
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use chrono::prelude::*;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::error::{Error, Result};
+use crate::transport::{Request, Transport};
+
+pub const AUTH_URL: &str = "https://id.twitch.tv/oauth2";
+
+/// Tokens this close to expiring are treated as expired already
+const EXPIRY_MARGIN_SECS: i64 = 60;
+
+/// Twitch asks for tokens to be validated hourly, no need to do it more
+const VALIDATION_TTL_SECS: i64 = 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Token {
+    pub client_id: String,
+    pub access_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: i64,
+}
+
+impl Token {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at - Duration::seconds(EXPIRY_MARGIN_SECS) <= Utc::now()
+    }
+
+    /// Read a cached token, if there is one for this client id that
+    /// hasn't expired yet
+    pub fn load(path: &Path, client_id: &str) -> Option<Token> {
+        let json = fs::read_to_string(path).ok()?;
+        let token = serde_json::from_str::<Token>(&json).ok()?;
+        if token.client_id != client_id || token.is_expired() {
+            return None;
+        }
+        Some(token)
+    }
+
+    /// Best effort, a token that can't be cached is still a usable token
+    pub fn save(&self, path: &Path) {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string(self) {
+            let _ = write_private(path, &json);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(contents.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    fs::write(path, contents)
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    let cache_dir = env::var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| env::var("HOME").map(|home| Path::new(&home).join(".cache")))
+        .ok()?;
+
+    Some(cache_dir.join("twitch-search"))
+}
+
+/// `TWITCH_TOKEN_CACHE`, or `token.json` in the user's cache directory
+pub fn default_cache_path() -> Option<PathBuf> {
+    if let Ok(path) = env::var("TWITCH_TOKEN_CACHE") {
+        return Some(PathBuf::from(path));
+    }
+
+    Some(cache_dir()?.join("token.json"))
+}
+
+/// `validation.json` in the user's cache directory
+pub fn default_validation_cache_path() -> Option<PathBuf> {
+    Some(cache_dir()?.join("validation.json"))
+}
+
+/// Exchange the client id and secret for a fresh app access token
+pub fn request_token(
+    transport: &dyn Transport,
+    auth_base: &str,
+    client_id: &str,
+    client_secret: &str,
+) -> Result<Token> {
+    let url = format!("{}/token", auth_base.trim_end_matches('/'));
+    let url = Url::parse(&url).map_err(|e| Error::InvalidUrl(format!("{url}: {e}")))?;
+
+    let request = Request::post_form(
+        url,
+        &[
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+            ("grant_type", "client_credentials"),
+        ],
+    );
+
+    let resp = transport.send(&request)?;
+    if !(200..300).contains(&resp.status) {
+        return Err(Error::from_response(&resp));
+    }
+
+    let token = serde_json::from_str::<TokenResponse>(&resp.body)
+        .map_err(|e| Error::Json(e.to_string()))?;
+
+    Ok(Token {
+        client_id: client_id.to_string(),
+        access_token: token.access_token,
+        expires_at: Utc::now() + Duration::seconds(token.expires_in),
+    })
+}
+
+// -----------------------------------------------------------------------------
+//     - Validation -
+//     https://dev.twitch.tv/docs/authentication/validate-tokens/
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Validation {
+    pub client_id: String,
+    /// Only user access tokens have a login, app access tokens don't
+    pub login: Option<String>,
+    pub user_id: Option<String>,
+    pub scopes: Vec<String>,
+    /// `None` for tokens that never expire
+    pub expires_at: Option<DateTime<Utc>>,
+    pub checked_at: DateTime<Utc>,
+    /// Which token this is about, without writing the token itself to disk
+    token_hash: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ValidateResponse {
+    client_id: String,
+    login: Option<String>,
+    user_id: Option<String>,
+    #[serde(default)]
+    scopes: Option<Vec<String>>,
+    expires_in: i64,
+}
+
+fn token_hash(access_token: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    access_token.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Validation {
+    /// Checked long enough ago that it should be checked again
+    pub fn is_stale(&self) -> bool {
+        self.checked_at + Duration::seconds(VALIDATION_TTL_SECS) <= Utc::now()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| at <= Utc::now())
+    }
+
+    /// Read a cached validation of this token, however old it is
+    pub fn load(path: &Path, access_token: &str) -> Option<Validation> {
+        let json = fs::read_to_string(path).ok()?;
+        let validation = serde_json::from_str::<Validation>(&json).ok()?;
+        if validation.token_hash != token_hash(access_token) {
+            return None;
+        }
+        Some(validation)
+    }
+
+    /// Best effort, like [`Token::save`]
+    pub fn save(&self, path: &Path) {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Ok(json) = serde_json::to_string(self) {
+            let _ = write_private(path, &json);
+        }
+    }
+}
+
+/// Ask Twitch what it knows about a token. A token it no longer accepts
+/// is reported as [`Error::TokenExpired`].
+pub fn validate(
+    transport: &dyn Transport,
+    auth_base: &str,
+    access_token: &str,
+) -> Result<Validation> {
+    let url = format!("{}/validate", auth_base.trim_end_matches('/'));
+    let url = Url::parse(&url).map_err(|e| Error::InvalidUrl(format!("{url}: {e}")))?;
+
+    let request = Request::get(url).header("Authorization", &format!("OAuth {}", access_token));
+
+    let resp = transport.send(&request)?;
+    if resp.status == 401 {
+        return Err(Error::TokenExpired(None));
+    }
+    if !(200..300).contains(&resp.status) {
+        return Err(Error::from_response(&resp));
+    }
+
+    let validation = serde_json::from_str::<ValidateResponse>(&resp.body)
+        .map_err(|e| Error::Json(e.to_string()))?;
+
+    let now = Utc::now();
+    let expires_at = match validation.expires_in {
+        0 => None,
+        secs => Some(now + Duration::seconds(secs)),
+    };
+
+    Ok(Validation {
+        client_id: validation.client_id,
+        login: validation.login,
+        user_id: validation.user_id,
+        scopes: validation.scopes.unwrap_or_default(),
+        expires_at,
+        checked_at: now,
+        token_hash: token_hash(access_token),
+    })
+}
@@ -0,0 +1,445 @@
+use std::cell::{Cell, RefCell};
+use std::env;
+use std::path::PathBuf;
+use std::thread;
+
+use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::auth::{self, Token, Validation, AUTH_URL};
+use crate::entry::Entry;
+use crate::error::{Error, Result};
+use crate::helix::{Game, Response, Stream};
+use crate::ratelimit::{RateLimit, RetryPolicy, Stats};
+use crate::transport::{self, Request, Transport, UreqTransport};
+
+const ROOT_URL: &str = "https://api.twitch.tv/helix";
+
+/// One page of streams and the cursor for the next, if there is one
+#[derive(Debug)]
+pub struct Page {
+    pub entries: Vec<Entry>,
+    pub cursor: Option<String>,
+}
+
+enum Credentials {
+    /// A token handed to us, used as is
+    Token(String),
+    /// Client credentials, exchanged for app access tokens as needed
+    ClientSecret {
+        secret: String,
+        token: RefCell<Option<Token>>,
+        cache: Option<PathBuf>,
+    },
+}
+
+/// What to ask `/streams` for
+#[derive(Debug, Clone, Default)]
+pub struct StreamParams {
+    pub game_id: Option<String>,
+    /// Language codes, Helix accepts up to 100
+    pub languages: Vec<String>,
+    /// Helix accepts up to 100
+    pub user_logins: Vec<String>,
+}
+
+/// Lazily walks the pages of a category, fetching each one only when
+/// it's asked for
+pub struct Pages<'a> {
+    client: &'a Client,
+    params: StreamParams,
+    cursor: Option<String>,
+    done: bool,
+}
+
+impl Pages<'_> {
+    /// True once the last page has been fetched
+    pub fn is_exhausted(&self) -> bool {
+        self.done
+    }
+}
+
+impl Iterator for Pages<'_> {
+    type Item = Result<Page>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let page = self.client.fetch(&self.params, self.cursor.as_deref());
+        match &page {
+            Ok(page) => {
+                self.cursor = page.cursor.clone();
+                self.done = self.cursor.is_none();
+            }
+            Err(_) => self.done = true,
+        }
+        Some(page)
+    }
+}
+
+/// Credentials for the Helix api and the transport to reach it with
+pub struct Client {
+    client_id: String,
+    credentials: Credentials,
+    api_base: String,
+    auth_base: String,
+    validation_cache: Option<PathBuf>,
+    transport: Box<dyn Transport>,
+    retry: RetryPolicy,
+    rate_limit: Cell<Option<RateLimit>>,
+    stats: RefCell<Stats>,
+}
+
+impl Client {
+    pub fn new(client_id: impl Into<String>, token: impl Into<String>) -> Self {
+        Self::with_credentials(client_id.into(), Credentials::Token(token.into()))
+    }
+
+    /// A client that fetches (and refreshes) its own app access token
+    pub fn from_secret(client_id: impl Into<String>, secret: impl Into<String>) -> Self {
+        let credentials = Credentials::ClientSecret {
+            secret: secret.into(),
+            token: RefCell::new(None),
+            cache: None,
+        };
+        Self::with_credentials(client_id.into(), credentials)
+    }
+
+    fn with_credentials(client_id: String, credentials: Credentials) -> Self {
+        Self {
+            client_id,
+            credentials,
+            api_base: ROOT_URL.to_string(),
+            auth_base: AUTH_URL.to_string(),
+            validation_cache: None,
+            transport: Box::new(UreqTransport::new()),
+            retry: RetryPolicy::default(),
+            rate_limit: Cell::new(None),
+            stats: RefCell::new(Stats::default()),
+        }
+    }
+
+    /// Read the credentials from `TWITCH_CLIENT_ID` and either
+    /// `TWITCH_CLIENT_SECRET` or `TWITCH_TOKEN`, and the api and auth base
+    /// urls from `TWITCH_API_BASE` and `TWITCH_AUTH_BASE` if set
+    pub fn from_env() -> Result<Self> {
+        let client_id = env::var("TWITCH_CLIENT_ID")
+            .map_err(|_| Error::MissingCredentials("TWITCH_CLIENT_ID"))?;
+
+        let mut client = match env::var("TWITCH_CLIENT_SECRET") {
+            Ok(secret) => {
+                let client = Self::from_secret(client_id, secret);
+                match auth::default_cache_path() {
+                    Some(path) => client.with_token_cache(path),
+                    None => client,
+                }
+            }
+            Err(_) => {
+                let token = env::var("TWITCH_TOKEN")
+                    .map_err(|_| Error::MissingCredentials("TWITCH_TOKEN"))?;
+                Self::new(client_id, token)
+            }
+        };
+
+        if let Some(path) = auth::default_validation_cache_path() {
+            client = client.with_validation_cache(path);
+        }
+        if let Ok(api_base) = env::var("TWITCH_API_BASE") {
+            client = client.with_api_base(api_base);
+        }
+        if let Ok(auth_base) = env::var("TWITCH_AUTH_BASE") {
+            client = client.with_auth_base(auth_base);
+        }
+
+        Ok(client)
+    }
+
+    /// Talk to something other than `https://api.twitch.tv/helix`
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into();
+        self
+    }
+
+    /// Get tokens from somewhere other than `https://id.twitch.tv/oauth2`
+    pub fn with_auth_base(mut self, auth_base: impl Into<String>) -> Self {
+        self.auth_base = auth_base.into();
+        self
+    }
+
+    /// Where app access tokens are cached between runs.
+    /// Has no effect on clients created with a token.
+    pub fn with_token_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        if let Credentials::ClientSecret { cache, .. } = &mut self.credentials {
+            *cache = Some(path.into());
+        }
+        self
+    }
+
+    /// Where the result of [`Client::preflight`] is kept between runs
+    pub fn with_validation_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.validation_cache = Some(path.into());
+        self
+    }
+
+    pub fn with_transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Box::new(transport);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Requests, retries and waiting done so far
+    pub fn stats(&self) -> Stats {
+        self.stats.borrow().clone()
+    }
+
+    fn endpoint(&self, path: &str, params: &[(&str, &str)]) -> Result<Url> {
+        let url = format!("{}/{}", self.api_base.trim_end_matches('/'), path);
+        Url::parse_with_params(&url, params).map_err(|e| Error::InvalidUrl(format!("{url}: {e}")))
+    }
+
+    // -----------------------------------------------------------------------------
+    //     - Token -
+    //     In memory first, then the disk cache, then a new one from Twitch
+    // -----------------------------------------------------------------------------
+    fn access_token(&self) -> Result<String> {
+        let (token, cache) = match &self.credentials {
+            Credentials::Token(token) => return Ok(token.clone()),
+            Credentials::ClientSecret { token, cache, .. } => (token, cache),
+        };
+
+        if let Some(token) = token.borrow().as_ref().filter(|t| !t.is_expired()) {
+            return Ok(token.access_token.clone());
+        }
+
+        let cached = cache
+            .as_deref()
+            .and_then(|path| Token::load(path, &self.client_id));
+        let access_token = match cached {
+            Some(cached) => {
+                let access_token = cached.access_token.clone();
+                token.replace(Some(cached));
+                access_token
+            }
+            None => self.refresh_token()?,
+        };
+
+        Ok(access_token)
+    }
+
+    /// Get a new app access token. Clients that were given a token have
+    /// no way of getting another one and get the same one back.
+    fn refresh_token(&self) -> Result<String> {
+        let (secret, token, cache) = match &self.credentials {
+            Credentials::Token(token) => return Ok(token.clone()),
+            Credentials::ClientSecret {
+                secret,
+                token,
+                cache,
+            } => (secret, token, cache),
+        };
+
+        let fresh = auth::request_token(
+            self.transport.as_ref(),
+            &self.auth_base,
+            &self.client_id,
+            secret,
+        )?;
+        if let Some(path) = cache {
+            fresh.save(path);
+        }
+
+        let access_token = fresh.access_token.clone();
+        token.replace(Some(fresh));
+        Ok(access_token)
+    }
+
+    // -----------------------------------------------------------------------------
+    //     - Validation -
+    // -----------------------------------------------------------------------------
+    /// Ask Twitch about the token currently in use
+    pub fn validate(&self) -> Result<Validation> {
+        auth::validate(
+            self.transport.as_ref(),
+            &self.auth_base,
+            &self.access_token()?,
+        )
+    }
+
+    /// Make sure a user supplied token is still good before searching, so
+    /// an expired one gets a clear error rather than a 401 halfway through.
+    /// Results are cached for an hour. Clients with a client secret look
+    /// after their own tokens and skip the check.
+    pub fn preflight(&self) -> Result<Option<Validation>> {
+        let token = match &self.credentials {
+            Credentials::Token(token) => token,
+            Credentials::ClientSecret { .. } => return Ok(None),
+        };
+
+        let cached = self
+            .validation_cache
+            .as_deref()
+            .and_then(|path| Validation::load(path, token));
+
+        if let Some(cached) = &cached {
+            if cached.is_expired() {
+                return Err(Error::TokenExpired(cached.expires_at));
+            }
+            if !cached.is_stale() {
+                return Ok(Some(cached.clone()));
+            }
+        }
+
+        match auth::validate(self.transport.as_ref(), &self.auth_base, token) {
+            Ok(validation) => {
+                if let Some(path) = &self.validation_cache {
+                    validation.save(path);
+                }
+                Ok(Some(validation))
+            }
+            Err(Error::TokenExpired(None)) => {
+                Err(Error::TokenExpired(cached.and_then(|c| c.expires_at)))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn wait(&self, delay: std::time::Duration) {
+        self.stats.borrow_mut().waited += delay;
+        thread::sleep(delay);
+    }
+
+    /// Sit out the rest of the minute if the bucket is nearly empty
+    fn throttle(&self) {
+        let rate_limit = match self.rate_limit.get() {
+            Some(rate_limit) if rate_limit.remaining <= self.retry.min_remaining => rate_limit,
+            _ => return,
+        };
+
+        let delay = rate_limit.until_reset().min(self.retry.max_delay);
+        if !delay.is_zero() {
+            self.stats.borrow_mut().pauses += 1;
+            self.wait(delay);
+        }
+        self.rate_limit.set(None);
+    }
+
+    fn send(&self, url: &Url, token: &str) -> Result<transport::Response> {
+        self.throttle();
+
+        let request = Request::get(url.clone())
+            .header("Authorization", &format!("Bearer {}", token))
+            .header("Client-Id", &self.client_id);
+        let resp = self.transport.send(&request)?;
+
+        self.stats.borrow_mut().requests += 1;
+        if let Some(rate_limit) = RateLimit::from_response(&resp) {
+            self.rate_limit.set(Some(rate_limit));
+        }
+
+        Ok(resp)
+    }
+
+    fn get<T: DeserializeOwned>(&self, url: Url) -> Result<Response<T>> {
+        let mut attempt = 0;
+        let resp = loop {
+            let mut resp = self.send(&url, &self.access_token()?)?;
+
+            // App access tokens can be revoked before they expire, so get a
+            // new one and try again once.
+            if resp.status == 401 && matches!(self.credentials, Credentials::ClientSecret { .. }) {
+                resp = self.send(&url, &self.refresh_token()?)?;
+            }
+
+            if !self.retry.should_retry(resp.status, attempt) {
+                break resp;
+            }
+
+            // A 429 means the bucket is empty, no point in trying again
+            // before it refills.
+            let mut delay = self.retry.backoff(attempt);
+            if resp.status == 429 {
+                if let Some(rate_limit) = RateLimit::from_response(&resp) {
+                    delay = delay.max(rate_limit.until_reset().min(self.retry.max_delay));
+                }
+                self.rate_limit.set(None);
+            }
+
+            *self
+                .stats
+                .borrow_mut()
+                .retries
+                .entry(resp.status)
+                .or_default() += 1;
+            self.wait(delay);
+            attempt += 1;
+        };
+
+        if !(200..300).contains(&resp.status) {
+            return Err(Error::from_response(&resp));
+        }
+
+        serde_json::from_str(&resp.body).map_err(|e| Error::Json(e.to_string()))
+    }
+
+    // -----------------------------------------------------------------------------
+    //     - Category -
+    //     Numeric categories are game ids, anything else is looked up by name
+    // -----------------------------------------------------------------------------
+    pub fn resolve_category(&self, category: &str) -> Result<String> {
+        if category.chars().all(|c| c.is_ascii_digit()) {
+            return Ok(category.to_string());
+        }
+
+        let games = self.get::<Game>(self.endpoint("games", &[("name", category)])?)?;
+
+        match games.data.unwrap_or_default().into_iter().next() {
+            Some(game) => Ok(game.id),
+            None => Err(Error::UnknownCategory(category.to_string())),
+        }
+    }
+
+    /// All pages of live streams matching the params
+    pub fn pages(&self, params: StreamParams) -> Pages<'_> {
+        Pages {
+            client: self,
+            params,
+            cursor: None,
+            done: false,
+        }
+    }
+
+    /// Fetch a single page of live streams
+    pub fn fetch(&self, stream_params: &StreamParams, after: Option<&str>) -> Result<Page> {
+        let mut params = vec![("first", "100")];
+        if let Some(game_id) = &stream_params.game_id {
+            params.push(("game_id", game_id));
+        }
+        for language in &stream_params.languages {
+            params.push(("language", language));
+        }
+        for login in &stream_params.user_logins {
+            params.push(("user_login", login));
+        }
+        if let Some(after) = after {
+            params.push(("after", after));
+        }
+
+        let streams = self.get::<Stream>(self.endpoint("streams", &params)?)?;
+
+        let entries = match streams.data {
+            Some(data) => data.into_iter().map(Entry::from).collect(),
+            None => return Err(Error::MissingField("data")),
+        };
+
+        Ok(Page {
+            entries,
+            cursor: streams.pagination.cursor,
+        })
+    }
+}
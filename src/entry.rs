@@ -0,0 +1,52 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use serde::Serialize;
+
+use crate::helix::Stream;
+
+/// A live stream, trimmed down to what the search cares about
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub user_id: String,
+    pub login: String,
+    pub lang: String,
+    pub display_name: String,
+    /// The category's name
+    pub game_name: String,
+    pub title: String,
+    pub viewer_count: i64,
+    /// `None` if Twitch didn't say, or said something unparseable
+    pub started_at: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    pub is_mature: bool,
+    /// Content classification label ids, e.g. "Gambling"
+    pub labels: Vec<String>,
+    /// How well the entry matched the search, 1.0 unless fuzzy matching
+    pub score: f64,
+}
+
+impl From<Stream> for Entry {
+    fn from(stream: Stream) -> Self {
+        Entry {
+            user_id: stream.user_id,
+            login: stream.user_login,
+            lang: stream.language,
+            display_name: stream.user_name,
+            game_name: stream.game_name,
+            title: stream.title.replace('\n', "…"),
+            viewer_count: stream.viewer_count,
+            started_at: stream.started_at,
+            tags: stream.tags,
+            is_mature: stream.is_mature,
+            labels: stream.content_classification_labels,
+            score: 1.0,
+        }
+    }
+}
+
+impl Entry {
+    /// How long the stream has been live
+    pub fn uptime(&self) -> Option<Duration> {
+        self.started_at.map(|started_at| Utc::now() - started_at)
+    }
+}
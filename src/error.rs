@@ -0,0 +1,132 @@
+use std::fmt;
+
+use chrono::prelude::*;
+
+use crate::transport::Response;
+
+#[derive(Debug)]
+pub enum Error {
+    /// A required environment variable is not set
+    MissingCredentials(&'static str),
+    /// The token or client id was rejected (401)
+    Unauthorized(String),
+    /// The token failed validation, with when it expired if known
+    TokenExpired(Option<DateTime<Utc>>),
+    /// Too many requests (429), with the time the bucket refills if known
+    RateLimited(Option<DateTime<Utc>>),
+    /// Any other non-success status
+    Http { status: u16, message: String },
+    /// The request never got a response (dns, tls, connection reset...)
+    Transport(String),
+    /// The response body wasn't valid json
+    Json(String),
+    /// The response was json but a field we rely on was absent
+    MissingField(&'static str),
+    /// A category name that Twitch doesn't know about
+    UnknownCategory(String),
+    /// The api base url couldn't be parsed
+    InvalidUrl(String),
+    /// The search term isn't a valid regular expression
+    InvalidPattern(String),
+    /// The query couldn't be parsed, column is 1-based
+    InvalidQuery { column: usize, message: String },
+    /// A file given on the command line couldn't be read
+    Io { path: String, message: String },
+    /// Whatever was reading the output went away, e.g. `head`
+    BrokenPipe,
+    /// Writing the results failed
+    Output(String),
+}
+
+impl Error {
+    /// Exit code for the process, distinct per kind of failure so
+    /// scripts can tell an expired token from Twitch being down.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::MissingCredentials(_) => 3,
+            Error::Unauthorized(_) | Error::TokenExpired(_) => 4,
+            Error::RateLimited(_) => 5,
+            Error::Http { .. } => 6,
+            Error::Transport(_) => 7,
+            Error::Json(_) => 8,
+            Error::MissingField(_) => 9,
+            Error::UnknownCategory(_) => 10,
+            Error::InvalidUrl(_) => 11,
+            Error::InvalidPattern(_) => 12,
+            Error::InvalidQuery { .. } => 13,
+            Error::Io { .. } => 14,
+            // Not really a failure, the reader had all it wanted
+            Error::BrokenPipe => 0,
+            Error::Output(_) => 15,
+        }
+    }
+
+    /// Turn a non-success response into the matching error
+    pub fn from_response(resp: &Response) -> Self {
+        let reset = resp
+            .header("Ratelimit-Reset")
+            .and_then(|r| r.parse::<i64>().ok())
+            .and_then(|r| Utc.timestamp_opt(r, 0).single());
+
+        // Helix errors look like {"error":"..","status":401,"message":".."}
+        let message = serde_json::from_str::<serde_json::Value>(&resp.body)
+            .ok()
+            .and_then(|v| v.get("message")?.as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        match resp.status {
+            401 => Error::Unauthorized(message),
+            429 => Error::RateLimited(reset),
+            status => Error::Http { status, message },
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingCredentials(var) => write!(f, "Missing credentials: {} is not set", var),
+            Error::Unauthorized(msg) => write!(
+                f,
+                "Authentication failed: {} (has TWITCH_TOKEN expired?)",
+                msg
+            ),
+            Error::TokenExpired(Some(at)) => write!(
+                f,
+                "OAuth token expired at {}, TWITCH_TOKEN needs replacing",
+                at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            ),
+            Error::TokenExpired(None) => write!(
+                f,
+                "OAuth token is invalid or has expired, TWITCH_TOKEN needs replacing"
+            ),
+            Error::RateLimited(Some(reset)) => write!(
+                f,
+                "Rate limited by Twitch, try again after {}",
+                reset.with_timezone(&Local).format("%H:%M:%S")
+            ),
+            Error::RateLimited(None) => write!(f, "Rate limited by Twitch, try again later"),
+            Error::Http { status, message } => {
+                write!(f, "Twitch returned HTTP {}: {}", status, message)
+            }
+            Error::Transport(msg) => write!(f, "Request failed: {}", msg),
+            Error::Json(msg) => write!(f, "Malformed response: {}", msg),
+            Error::MissingField(field) => {
+                write!(f, "Malformed response: missing field \"{}\"", field)
+            }
+            Error::UnknownCategory(name) => write!(f, "Unknown category \"{}\"", name),
+            Error::InvalidUrl(msg) => write!(f, "Invalid url: {}", msg),
+            Error::InvalidPattern(msg) => write!(f, "Invalid regular expression:\n{}", msg),
+            Error::InvalidQuery { column, message } => {
+                write!(f, "Invalid query at column {}: {}", column, message)
+            }
+            Error::Io { path, message } => write!(f, "Couldn't read {}: {}", path, message),
+            Error::BrokenPipe => write!(f, "Output closed"),
+            Error::Output(msg) => write!(f, "Couldn't write the results: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,307 @@
+// -----------------------------------------------------------------------------
+//     - Query language -
+//     rust AND (bevy OR wgpu) NOT giveaway
+//
+//     Terms next to each other are ANDed. Operators are upper case so
+//     "and" and "or" can still be searched for. Quote phrases with spaces
+//     in them, and prefix a term with title:, lang:, user: or tag: to say
+//     what it's matched against (the title, if there's no prefix). Any
+//     other prefix is just part of the term.
+//
+//     In regex mode the whole input is one pattern, since spaces, quotes
+//     and parentheses all mean something in a regular expression.
+// -----------------------------------------------------------------------------
+use crate::entry::Entry;
+use crate::error::{Error, Result};
+use crate::filter::{MatchMode, Matcher};
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Title(Matcher),
+    /// Lowercase language code, e.g. "en"
+    Lang(String),
+    /// Lowercase login or display name
+    User(String),
+    /// Lowercase tag
+    Tag(String),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Expr {
+    /// Parse a query. Title terms are compiled with the given mode, in
+    /// regex mode the input is a single title pattern. An empty query is an
+    /// error, a `None` query in the [`Filter`](crate::Filter) matches everything.
+    pub fn parse(input: &str, mode: MatchMode, case_sensitive: bool) -> Result<Self> {
+        if mode == MatchMode::Regex {
+            return Ok(Expr::Title(Matcher::new(input, mode, case_sensitive)?));
+        }
+
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+            end: input.chars().count() + 1,
+            mode,
+            case_sensitive,
+        };
+
+        let expr = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(invalid(token.column, "unmatched \")\"")),
+        }
+    }
+
+    pub fn matches(&self, entry: &Entry) -> bool {
+        self.score(entry).is_some()
+    }
+
+    /// How well the entry matches, `None` if it doesn't. An AND is as good
+    /// as its worst side, an OR as its best.
+    pub fn score(&self, entry: &Entry) -> Option<f64> {
+        let exact = |matched: bool| Some(1.0).filter(|_| matched);
+
+        match self {
+            Expr::Title(matcher) => matcher.score(&entry.title),
+            Expr::Lang(lang) => exact(entry.lang.to_lowercase() == *lang),
+            Expr::User(user) => exact(
+                entry.login.to_lowercase() == *user || entry.display_name.to_lowercase() == *user,
+            ),
+            Expr::Tag(tag) => exact(entry.tags.iter().any(|t| t.to_lowercase() == *tag)),
+            Expr::And(lhs, rhs) => Some(lhs.score(entry)?.min(rhs.score(entry)?)),
+            Expr::Or(lhs, rhs) => match (lhs.score(entry), rhs.score(entry)) {
+                (Some(l), Some(r)) => Some(l.max(r)),
+                (l, r) => l.or(r),
+            },
+            Expr::Not(expr) => exact(expr.score(entry).is_none()),
+        }
+    }
+}
+
+fn invalid(column: usize, message: impl Into<String>) -> Error {
+    Error::InvalidQuery {
+        column,
+        message: message.into(),
+    }
+}
+
+// -----------------------------------------------------------------------------
+//     - Tokens -
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Title,
+    Lang,
+    User,
+    Tag,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "title" => Some(Field::Title),
+            "lang" => Some(Field::Lang),
+            "user" => Some(Field::User),
+            "tag" => Some(Field::Tag),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    Open,
+    Close,
+    And,
+    Or,
+    Not,
+    Term(Field, String),
+}
+
+#[derive(Debug)]
+struct Token {
+    kind: Kind,
+    /// 1-based, in characters
+    column: usize,
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '"')
+}
+
+/// A quoted phrase starting at `start`, and the index after it
+fn quoted(chars: &[char], start: usize) -> Result<(String, usize)> {
+    let close = chars[start + 1..]
+        .iter()
+        .position(|&c| c == '"')
+        .map(|p| start + 1 + p)
+        .ok_or_else(|| invalid(start + 1, "unterminated quote"))?;
+
+    let phrase = chars[start + 1..close].iter().collect::<String>();
+    if phrase.trim().is_empty() {
+        return Err(invalid(start + 1, "empty phrase"));
+    }
+
+    Ok((phrase, close + 1))
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let column = i + 1;
+        let kind = match chars[i] {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => {
+                i += 1;
+                Kind::Open
+            }
+            ')' => {
+                i += 1;
+                Kind::Close
+            }
+            '"' => {
+                let (phrase, next) = quoted(&chars, i)?;
+                i = next;
+                Kind::Term(Field::Title, phrase)
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && !is_delimiter(chars[i]) {
+                    i += 1;
+                }
+                let word = chars[start..i].iter().collect::<String>();
+
+                match word.as_str() {
+                    "AND" => Kind::And,
+                    "OR" => Kind::Or,
+                    "NOT" => Kind::Not,
+                    // Only the known field names are taken as one, so
+                    // "12:00" or "re:zero" are still plain terms
+                    _ => match word
+                        .split_once(':')
+                        .and_then(|(name, value)| Some((name, Field::from_name(name)?, value)))
+                    {
+                        Some((name, field, value)) => {
+                            if !value.is_empty() {
+                                Kind::Term(field, value.to_string())
+                            } else if chars.get(i) == Some(&'"') {
+                                let (phrase, next) = quoted(&chars, i)?;
+                                i = next;
+                                Kind::Term(field, phrase)
+                            } else {
+                                return Err(invalid(
+                                    i + 1,
+                                    format!("expected a value after \"{}:\"", name),
+                                ));
+                            }
+                        }
+                        None => Kind::Term(Field::Title, word),
+                    },
+                }
+            }
+        };
+        tokens.push(Token { kind, column });
+    }
+
+    Ok(tokens)
+}
+
+// -----------------------------------------------------------------------------
+//     - Parser -
+//     or    = and ("OR" and)*
+//     and   = unary ("AND"? unary)*
+//     unary = "NOT" unary | "(" or ")" | term
+// -----------------------------------------------------------------------------
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    /// Column just past the end of the input, for errors at the end
+    end: usize,
+    mode: MatchMode,
+    case_sensitive: bool,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, kind: Kind) -> bool {
+        match self.peek() {
+            Some(token) if token.kind == kind => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_and()?;
+        while self.eat(Kind::Or) {
+            let rhs = self.parse_and()?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_unary()?;
+        loop {
+            let implicit = matches!(
+                self.peek().map(|t| &t.kind),
+                Some(Kind::Open | Kind::Not | Kind::Term(..))
+            );
+            if !(self.eat(Kind::And) || implicit) {
+                break;
+            }
+            let rhs = self.parse_unary()?;
+            lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        let tokens = self.tokens;
+        let token = match tokens.get(self.pos) {
+            Some(token) => token,
+            None => return Err(invalid(self.end, "expected a term")),
+        };
+        let column = token.column;
+        self.pos += 1;
+
+        match &token.kind {
+            Kind::Not => Ok(Expr::Not(Box::new(self.parse_unary()?))),
+            Kind::Open => {
+                let expr = self.parse_or()?;
+                if !self.eat(Kind::Close) {
+                    let column = self.peek().map_or(self.end, |t| t.column);
+                    return Err(invalid(column, "expected \")\""));
+                }
+                Ok(expr)
+            }
+            Kind::Term(field, value) => self.term(*field, value),
+            Kind::Close => Err(invalid(column, "expected a term, found \")\"")),
+            Kind::And => Err(invalid(column, "expected a term, found AND")),
+            Kind::Or => Err(invalid(column, "expected a term, found OR")),
+        }
+    }
+
+    fn term(&self, field: Field, value: &str) -> Result<Expr> {
+        let expr = match field {
+            Field::Title => Expr::Title(Matcher::new(value, self.mode, self.case_sensitive)?),
+            Field::Lang => Expr::Lang(value.to_lowercase()),
+            Field::User => Expr::User(value.to_lowercase()),
+            Field::Tag => Expr::Tag(value.to_lowercase()),
+        };
+        Ok(expr)
+    }
+}
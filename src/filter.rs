@@ -0,0 +1,377 @@
+use chrono::Duration;
+use regex::{Regex, RegexBuilder};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::entry::Entry;
+use crate::error::{Error, Result};
+use crate::expr::Expr;
+
+/// Default similarity a fuzzy match needs, see [`MatchMode::Fuzzy`]
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// How a search term is compared against a title
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MatchMode {
+    /// Anywhere in the title
+    #[default]
+    Substring,
+    /// Only on word boundaries
+    Word,
+    /// The term is a regular expression
+    Regex,
+    /// Typo tolerant, scored by how similar the words of the title are to
+    /// the words of the term, from 0.0 to 1.0. Titles scoring below the
+    /// threshold don't match.
+    Fuzzy { threshold: f64 },
+}
+
+impl MatchMode {
+    /// Lowercase name, as in the command line flags
+    pub fn name(self) -> &'static str {
+        match self {
+            MatchMode::Substring => "substring",
+            MatchMode::Word => "word",
+            MatchMode::Regex => "regex",
+            MatchMode::Fuzzy { .. } => "fuzzy",
+        }
+    }
+}
+
+/// A compiled search term
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    Substring {
+        term: String,
+        case_sensitive: bool,
+    },
+    Word {
+        words: Vec<String>,
+        case_sensitive: bool,
+    },
+    Regex(Regex),
+    Fuzzy {
+        words: Vec<String>,
+        threshold: f64,
+        case_sensitive: bool,
+    },
+}
+
+impl Matcher {
+    pub fn new(term: &str, mode: MatchMode, case_sensitive: bool) -> Result<Self> {
+        let fold = |term: &str| match case_sensitive {
+            true => term.to_string(),
+            false => term.to_lowercase(),
+        };
+
+        let matcher = match mode {
+            MatchMode::Substring => Matcher::Substring {
+                term: fold(term),
+                case_sensitive,
+            },
+            MatchMode::Word => Matcher::Word {
+                words: words(term, case_sensitive),
+                case_sensitive,
+            },
+            MatchMode::Regex => {
+                let regex = RegexBuilder::new(term)
+                    .case_insensitive(!case_sensitive)
+                    .build()
+                    .map_err(|e| Error::InvalidPattern(e.to_string()))?;
+                Matcher::Regex(regex)
+            }
+            MatchMode::Fuzzy { threshold } => Matcher::Fuzzy {
+                words: fuzzy_words(term, case_sensitive),
+                threshold,
+                case_sensitive,
+            },
+        };
+
+        Ok(matcher)
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.score(text).is_some()
+    }
+
+    /// How well the text matches, `None` if it doesn't. Only fuzzy
+    /// matching has degrees, everything else is 1.0 on a match.
+    pub fn score(&self, text: &str) -> Option<f64> {
+        let matched = match self {
+            Matcher::Substring {
+                term,
+                case_sensitive,
+            } => match case_sensitive {
+                true => text.contains(term.as_str()),
+                false => text.to_lowercase().contains(term.as_str()),
+            },
+            Matcher::Word {
+                words: term,
+                case_sensitive,
+            } => {
+                !term.is_empty()
+                    && words(text, *case_sensitive)
+                        .windows(term.len())
+                        .any(|w| w == term.as_slice())
+            }
+            Matcher::Regex(regex) => regex.is_match(text),
+            Matcher::Fuzzy {
+                words,
+                threshold,
+                case_sensitive,
+            } => {
+                let score = fuzzy_score(words, &fuzzy_words(text, *case_sensitive));
+                return Some(score).filter(|score| *score >= *threshold);
+            }
+        };
+
+        Some(1.0).filter(|_| matched)
+    }
+}
+
+// -----------------------------------------------------------------------------
+//     - Words -
+//     Unicode word boundaries (UAX #29), so "rust2024" is one word, "C++"
+//     is "c", "+", "+" and every CJK ideograph is a word of its own. A term
+//     matches if its words show up in the title in the same order.
+// -----------------------------------------------------------------------------
+fn words(text: &str, case_sensitive: bool) -> Vec<String> {
+    text.split_word_bounds()
+        .filter(|w| !w.trim().is_empty())
+        .map(|w| {
+            let w = match case_sensitive {
+                true => w.to_string(),
+                false => w.to_lowercase(),
+            };
+            // gamedev's -> gamedev
+            match w.strip_suffix("'s").or_else(|| w.strip_suffix("’s")) {
+                Some(stem) if !stem.is_empty() => stem.to_string(),
+                _ => w,
+            }
+        })
+        .collect()
+}
+
+// -----------------------------------------------------------------------------
+//     - Fuzzy -
+//     Every word of the term is paired with its most similar word in the
+//     title, and the score is the average similarity of those pairs.
+//     Punctuation and emoji aren't words, so they don't get in the way.
+// -----------------------------------------------------------------------------
+fn fuzzy_words(text: &str, case_sensitive: bool) -> Vec<String> {
+    text.unicode_words()
+        .map(|w| match case_sensitive {
+            true => w.to_string(),
+            false => w.to_lowercase(),
+        })
+        .collect()
+}
+
+fn fuzzy_score(term: &[String], title: &[String]) -> f64 {
+    if term.is_empty() {
+        return 0.0;
+    }
+
+    let total = term
+        .iter()
+        .map(|t| title.iter().map(|w| similarity(t, w)).fold(0.0, f64::max))
+        .sum::<f64>();
+
+    total / term.len() as f64
+}
+
+/// 1.0 for identical words, down to 0.0 for nothing in common
+fn similarity(a: &str, b: &str) -> f64 {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / (2 * len) as f64
+}
+
+/// Optimal string alignment distance in half edits. Inserting, deleting or
+/// changing a character is a whole edit, swapping two neighbours only half
+/// of one: "rsut" is a typo of "rust", "just" is another word.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = 2 * i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = 2 * j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = 2 * usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 2)
+                .min(d[i][j - 1] + 2)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+/// Decides which entries make it into the results
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// What to look for, `None` matches everything
+    pub query: Option<Expr>,
+    /// Lowercase names of streamers to leave out
+    pub ignored_names: Vec<String>,
+    /// Titles matching any of these are left out
+    pub excluded_terms: Vec<Matcher>,
+    /// Lowercase language codes to keep, empty keeps all
+    pub languages: Vec<String>,
+    /// Lowercase tags a stream must have all of
+    pub tags: Vec<String>,
+    /// Lowercase tags a stream must have at least one of, empty keeps all
+    pub any_tags: Vec<String>,
+    /// Lowercase tags that leave a stream out
+    pub excluded_tags: Vec<String>,
+    /// Leave out streams marked as mature
+    pub no_mature: bool,
+    /// Lowercase content classification labels that leave a stream out
+    pub excluded_labels: Vec<String>,
+    pub min_viewers: Option<i64>,
+    pub max_viewers: Option<i64>,
+    /// Streams with an unknown start time never pass an uptime filter
+    pub min_uptime: Option<Duration>,
+    pub max_uptime: Option<Duration>,
+}
+
+impl Filter {
+    pub fn matches(&self, entry: &Entry) -> bool {
+        self.score(entry).is_some()
+    }
+
+    /// How well the entry matches, `None` if it doesn't
+    pub fn score(&self, entry: &Entry) -> Option<f64> {
+        if self
+            .ignored_names
+            .contains(&entry.display_name.to_lowercase())
+        {
+            return None;
+        }
+
+        if self.excluded_terms.iter().any(|t| t.is_match(&entry.title)) {
+            return None;
+        }
+
+        if !self.languages.is_empty() && !self.languages.contains(&entry.lang.to_lowercase()) {
+            return None;
+        }
+
+        if !self.has_tags(entry) {
+            return None;
+        }
+
+        if self.no_mature && entry.is_mature {
+            return None;
+        }
+
+        if entry
+            .labels
+            .iter()
+            .any(|l| self.excluded_labels.contains(&l.to_lowercase()))
+        {
+            return None;
+        }
+
+        if !self.in_range(entry) {
+            return None;
+        }
+
+        match &self.query {
+            Some(query) => query.score(entry),
+            None => Some(1.0),
+        }
+    }
+
+    fn has_tags(&self, entry: &Entry) -> bool {
+        let tags = entry
+            .tags
+            .iter()
+            .map(|t| t.to_lowercase())
+            .collect::<Vec<_>>();
+
+        self.tags.iter().all(|t| tags.contains(t))
+            && (self.any_tags.is_empty() || self.any_tags.iter().any(|t| tags.contains(t)))
+            && !self.excluded_tags.iter().any(|t| tags.contains(t))
+    }
+
+    fn in_range(&self, entry: &Entry) -> bool {
+        let viewers = entry.viewer_count;
+        if self.min_viewers.is_some_and(|min| viewers < min)
+            || self.max_viewers.is_some_and(|max| viewers > max)
+        {
+            return false;
+        }
+
+        if self.min_uptime.is_none() && self.max_uptime.is_none() {
+            return true;
+        }
+        match entry.uptime() {
+            Some(uptime) => {
+                !(self.min_uptime.is_some_and(|min| uptime < min)
+                    || self.max_uptime.is_some_and(|max| uptime > max))
+            }
+            None => false,
+        }
+    }
+}
+
+/// Parse a fuzzy match threshold, from 0.0 to 1.0
+pub fn parse_threshold(input: &str) -> std::result::Result<f64, String> {
+    match input.trim().parse::<f64>() {
+        Ok(threshold) if (0.0..=1.0).contains(&threshold) => Ok(threshold),
+        _ => Err(format!(
+            "invalid threshold \"{}\", expected a number from 0.0 to 1.0",
+            input
+        )),
+    }
+}
+
+/// Parse durations like `45s`, `30m`, `2h`, `1d` or `1h30m`
+pub fn parse_duration(input: &str) -> std::result::Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "invalid duration \"{}\", expected e.g. 30m, 2h or 1h30m",
+            input
+        )
+    };
+
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    for c in input.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let n = digits.parse::<i64>().map_err(|_| invalid())?;
+        digits.clear();
+        let part = match c {
+            's' => Duration::try_seconds(n),
+            'm' => Duration::try_minutes(n),
+            'h' => Duration::try_hours(n),
+            'd' => Duration::try_days(n),
+            _ => None,
+        };
+        total = part
+            .and_then(|part| total.checked_add(&part))
+            .ok_or_else(invalid)?;
+    }
+
+    // A trailing number without a unit, or nothing at all
+    if !digits.is_empty() || input.trim().is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(total)
+}
@@ -0,0 +1,87 @@
+// -----------------------------------------------------------------------------
+//     - Helix models -
+//     https://dev.twitch.tv/docs/api/reference
+//
+//     Everything is optional or defaulted: Twitch sends nulls in places the
+//     docs don't mention and adds fields without notice, neither of which
+//     should take the whole search down.
+// -----------------------------------------------------------------------------
+use chrono::prelude::*;
+use serde::{Deserialize, Deserializer};
+
+/// The envelope every Helix list endpoint returns
+#[derive(Debug, Deserialize)]
+pub struct Response<T> {
+    pub data: Option<Vec<T>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub pagination: Pagination,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Pagination {
+    pub cursor: Option<String>,
+}
+
+/// An entry from `/streams`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Stream {
+    #[serde(deserialize_with = "nullable")]
+    pub id: String,
+    #[serde(deserialize_with = "nullable")]
+    pub user_id: String,
+    #[serde(deserialize_with = "nullable")]
+    pub user_login: String,
+    #[serde(deserialize_with = "nullable")]
+    pub user_name: String,
+    #[serde(deserialize_with = "nullable")]
+    pub game_id: String,
+    #[serde(deserialize_with = "nullable")]
+    pub game_name: String,
+    /// "live", or an empty string if something went wrong on Twitch's end
+    #[serde(rename = "type", deserialize_with = "nullable")]
+    pub kind: String,
+    #[serde(deserialize_with = "nullable")]
+    pub title: String,
+    #[serde(deserialize_with = "nullable")]
+    pub tags: Vec<String>,
+    #[serde(deserialize_with = "nullable")]
+    pub viewer_count: i64,
+    #[serde(deserialize_with = "timestamp")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(deserialize_with = "nullable")]
+    pub language: String,
+    #[serde(deserialize_with = "nullable")]
+    pub thumbnail_url: String,
+    #[serde(deserialize_with = "nullable")]
+    pub is_mature: bool,
+    /// Ids like "Gambling" or "ProfanityVulgarity"
+    #[serde(deserialize_with = "nullable")]
+    pub content_classification_labels: Vec<String>,
+}
+
+/// An entry from `/games`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Game {
+    #[serde(deserialize_with = "nullable")]
+    pub id: String,
+    #[serde(deserialize_with = "nullable")]
+    pub name: String,
+}
+
+fn nullable<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Option::unwrap_or_default)
+}
+
+fn timestamp<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let ds = Option::<String>::deserialize(deserializer)?;
+    Ok(ds.and_then(|ds| ds.parse().ok()))
+}
@@ -0,0 +1,92 @@
+// -----------------------------------------------------------------------------
+//     - Json output -
+//     Raw numbers rather than formatted text: viewer counts as numbers,
+//     start times in RFC 3339 and uptimes in seconds.
+// -----------------------------------------------------------------------------
+use chrono::prelude::*;
+use chrono::Duration;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::entry::Entry;
+use crate::filter::MatchMode;
+use crate::search::{Query, SearchResult};
+
+/// An entry with its uptime worked out, as it appears in the json output
+#[derive(Debug, Serialize)]
+pub struct Stream<'a> {
+    #[serde(flatten)]
+    pub entry: &'a Entry,
+    pub uptime_seconds: Option<i64>,
+}
+
+impl<'a> From<&'a Entry> for Stream<'a> {
+    fn from(entry: &'a Entry) -> Self {
+        Stream {
+            entry,
+            uptime_seconds: entry.uptime().map(|uptime| uptime.num_seconds()),
+        }
+    }
+}
+
+/// What the search was given as text, before it was compiled into the
+/// [`Query`]'s matchers
+#[derive(Debug, Clone, Copy)]
+pub struct Terms<'a> {
+    pub term: Option<&'a str>,
+    pub mode: MatchMode,
+    pub case_sensitive: bool,
+    /// Every excluded term, wherever it came from
+    pub excluded_terms: &'a [String],
+}
+
+/// The query, how the crawl went and every stream found, as one document
+pub fn document(
+    terms: Terms,
+    query: &Query,
+    result: &SearchResult,
+    started_at: DateTime<Utc>,
+) -> Value {
+    let filter = &query.filter;
+    let seconds = |duration: Option<Duration>| duration.map(|d| d.num_seconds());
+    // Looking up logins ignores the categories
+    let categories = match query.only.is_empty() {
+        true => query.categories.as_slice(),
+        false => &[],
+    };
+
+    json!({
+        "query": {
+            "term": terms.term,
+            "mode": terms.mode.name(),
+            "case_sensitive": terms.case_sensitive,
+            "categories": categories,
+            "only": query.only,
+            "excluded_names": filter.ignored_names,
+            "excluded_terms": terms.excluded_terms,
+            "languages": filter.languages,
+            "tags": filter.tags,
+            "any_tags": filter.any_tags,
+            "excluded_tags": filter.excluded_tags,
+            "no_mature": filter.no_mature,
+            "excluded_labels": filter.excluded_labels,
+            "min_viewers": filter.min_viewers,
+            "max_viewers": filter.max_viewers,
+            "min_uptime_seconds": seconds(filter.min_uptime),
+            "max_uptime_seconds": seconds(filter.max_uptime),
+            "sort": query.sort,
+            "reverse": query.reverse,
+            "limit": query.limit,
+            "fast": query.fast,
+        },
+        "crawl": {
+            "started_at": started_at,
+            "pages": result.pages,
+            "total": result.total,
+            "found": result.entries.len(),
+            "complete": result.complete,
+            "categories": result.categories,
+        },
+        "streams": result.entries.iter().map(Stream::from).collect::<Vec<_>>(),
+    })
+}
@@ -0,0 +1,44 @@
+//! Search the live streams of one or more Twitch categories.
+//!
+//! ```no_run
+//! use twitch_search::{search, Client, Expr, Filter, MatchMode, Query};
+//!
+//! let client = Client::from_env()?;
+//! let query = Query {
+//!     filter: Filter {
+//!         query: Some(Expr::parse("rust AND (bevy OR wgpu)", MatchMode::Word, false)?),
+//!         ..Filter::default()
+//!     },
+//!     ..Query::default()
+//! };
+//!
+//! for entry in search(&client, &query)?.entries {
+//!     println!("{} {}", entry.display_name, entry.title);
+//! }
+//! # Ok::<(), twitch_search::Error>(())
+//! ```
+pub mod auth;
+pub mod client;
+pub mod entry;
+pub mod error;
+pub mod expr;
+pub mod filter;
+pub mod helix;
+pub mod json;
+pub mod ratelimit;
+pub mod search;
+pub mod tags;
+pub mod transport;
+
+pub use client::{Client, Page, Pages, StreamParams};
+pub use entry::Entry;
+pub use error::{Error, Result};
+pub use expr::Expr;
+pub use filter::{Filter, MatchMode, Matcher, DEFAULT_THRESHOLD};
+pub use ratelimit::{RetryPolicy, Stats};
+pub use search::{search, search_each, CategoryTotal, Query, SearchResult, Sort};
+pub use tags::{popular_tags, TagCount};
+pub use transport::{Transport, UreqTransport};
+
+/// Science & Technology
+pub const DEFAULT_CATEGORY: &str = "1469308723";
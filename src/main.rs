@@ -1,228 +1,627 @@
 use std::env;
+use std::fs;
+use std::io::{self, Write};
 use std::process::exit;
+use std::str::FromStr;
+use std::time::{Duration as StdDuration, Instant};
 
 use chrono::prelude::*;
-use clap::Parser;
-use serde_json::Value;
-
-const ROOT_URL: &str = "https://api.twitch.tv/helix/streams?first=100&game_id=1469308723";
+use chrono::Duration;
+use clap::{CommandFactory, ErrorKind, Parser, Subcommand};
+use twitch_search::filter::{parse_duration, parse_threshold};
+use twitch_search::json::{self, Stream, Terms};
+use twitch_search::{
+    popular_tags, search_each, Client, Entry, Error, Expr, Filter, MatchMode, Matcher, Query,
+    Result, SearchResult, Sort, Stats, DEFAULT_CATEGORY, DEFAULT_THRESHOLD,
+};
+
+/// Warn about tokens expiring sooner than this
+const EXPIRY_WARNING_HOURS: i64 = 24;
 
 #[derive(Parser, Debug)]
 #[clap(about, version, author)]
 struct Args {
-    /// Term to search for
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// What to search for, e.g. rust AND (bevy OR wgpu) NOT giveaway.
+    /// Terms can be prefixed with title:, lang:, user: or tag:. Use
+    /// -- auth or -- tags to search for those words rather than run the
+    /// subcommands
     term: Option<String>,
 
     /// Streamers to exclude
-    #[clap(short = 'x', long)]
+    #[clap(short = 'x', long, global = true)]
     exclude: Option<Vec<String>>,
 
+    /// Leave out titles matching this, e.g. giveaway. Uses the same
+    /// matching as the search (--word, --regex...)
+    #[clap(long, global = true)]
+    exclude_term: Vec<String>,
+
     /// Search on word boundary
-    #[clap(short, long)]
+    #[clap(short, long, global = true)]
     word: bool,
 
+    /// Treat the whole term as one regular expression, without AND/OR/NOT
+    #[clap(short, long, conflicts_with = "word", global = true)]
+    regex: bool,
+
+    /// Typo tolerant matching, best matches first
+    #[clap(short, long, conflicts_with_all = &["word", "regex"], global = true)]
+    fuzzy: bool,
+
+    /// How similar a title has to be for a fuzzy match, from 0.0 to 1.0
+    #[clap(long, default_value_t = DEFAULT_THRESHOLD, parse(try_from_str = parse_threshold), global = true)]
+    threshold: f64,
+
+    /// Match upper and lower case exactly
+    #[clap(short = 's', long, global = true)]
+    case_sensitive: bool,
+
+    /// Only streams in these languages, e.g. --lang en --lang de
+    #[clap(long, global = true)]
+    lang: Vec<String>,
+
+    /// Only streams with this tag, repeat to require several
+    #[clap(long, global = true)]
+    tag: Vec<String>,
+
+    /// Only streams with at least one of these tags
+    #[clap(long, global = true)]
+    any_tag: Vec<String>,
+
+    /// Leave out streams with this tag
+    #[clap(long, global = true)]
+    exclude_tag: Vec<String>,
+
+    /// Leave out streams marked as mature, default from TWITCH_NO_MATURE
+    #[clap(long, global = true)]
+    no_mature: bool,
+
+    /// Leave out streams with this content classification label, e.g.
+    /// Gambling or ProfanityVulgarity. Added to TWITCH_EXCLUDE_LABELS
+    #[clap(long, global = true)]
+    exclude_label: Vec<String>,
+
+    /// Show each stream's tags
+    #[clap(long, global = true)]
+    show_tags: bool,
+
+    /// Only streams with at least this many viewers
+    #[clap(long, global = true)]
+    min_viewers: Option<i64>,
+
+    /// Only streams with at most this many viewers
+    #[clap(long, global = true)]
+    max_viewers: Option<i64>,
+
+    /// Only streams live for at least this long, e.g. 30m or 1h30m
+    #[clap(long, parse(try_from_str = parse_duration), global = true)]
+    min_uptime: Option<Duration>,
+
+    /// Only streams live for at most this long, e.g. 30m or 1h30m
+    #[clap(long, parse(try_from_str = parse_duration), global = true)]
+    max_uptime: Option<Duration>,
+
     /// limit output to n entries, 0 means all
-    #[clap(short, long, default_value = "0")]
+    #[clap(short, long, default_value = "0", global = true)]
     limit: usize,
-}
 
-macro_rules! to_str {
-    ($val: expr, $key: expr) => {
-        $val.get($key).unwrap().as_str().unwrap().to_string()
-    };
+    /// Categories to search, either game ids or category names
+    #[clap(short, long, default_value = DEFAULT_CATEGORY, global = true)]
+    category: Vec<String>,
+
+    /// Only look up these streamers instead of searching the categories,
+    /// e.g. --only alice,bob. They're found in any category
+    #[clap(
+        long,
+        use_value_delimiter = true,
+        conflicts_with = "category",
+        global = true
+    )]
+    only: Vec<String>,
+
+    /// Like --only, with one login per line read from a file
+    #[clap(long, conflicts_with = "category", global = true)]
+    only_file: Option<String>,
+
+    /// Show which category each stream is in
+    #[clap(long, global = true)]
+    show_category: bool,
+
+    /// Base url of the Helix api, overrides TWITCH_API_BASE
+    #[clap(long, global = true)]
+    api_base: Option<String>,
+
+    /// Stop as soon as --limit matches are found, the total is then a lower bound
+    #[clap(long, conflicts_with_all = &["sort", "reverse"], global = true)]
+    fast: bool,
+
+    /// Order results by, applied before --limit
+    #[clap(long, possible_values = Sort::NAMES, global = true)]
+    sort: Option<Sort>,
+
+    /// Reverse the sort order, or without --sort the order Twitch returned
+    #[clap(long, global = true)]
+    reverse: bool,
+
+    /// How to print the results: table, json for a single document or
+    /// ndjson for one line per stream as soon as it's found
+    #[clap(short, long, default_value = "table", possible_values = Output::NAMES, global = true)]
+    output: Output,
+
+    /// Show requests, retries and rate limit pauses
+    #[clap(short, long, global = true)]
+    verbose: bool,
 }
 
-macro_rules! to_num {
-    ($val: expr, $key: expr) => {
-        $val.get($key).unwrap().as_i64().unwrap()
-    };
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Inspect the configured credentials
+    Auth {
+        #[clap(subcommand)]
+        command: AuthCommand,
+    },
+    /// Rank the tags of the matching streams by how often they're used.
+    /// --limit caps the number of tags
+    Tags,
 }
 
-fn to_instant(ds: &str) -> String {
-    match ds.parse::<DateTime<Utc>>() {
-        Ok(val) => {
-            let dur = Utc::now() - val;
-            format!("{:02}:{:02}", dur.num_hours(), dur.num_minutes() % 60)
-        }
-        Err(_e) => "".to_string(),
-    }
+#[derive(Subcommand, Debug)]
+enum AuthCommand {
+    /// Validate the token and show what it's for and when it expires
+    Status,
 }
 
-#[derive(Debug)]
-struct Entry {
-    lang: String,
-    display_name: String,
-    title: String,
-    viewer_count: i64,
-    live_duration: String,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Table,
+    Json,
+    /// Streamed, so can't be sorted
+    Ndjson,
 }
 
-fn filter(entry: &Entry, word: bool, term: &Option<String>, ignored_names: &[String]) -> bool {
-    if ignored_names.contains(&entry.display_name.to_lowercase()) {
-        return false;
-    }
-    if term.is_none() {
-        return true;
-    }
+impl Output {
+    const NAMES: &'static [&'static str] = &["table", "json", "ndjson"];
+}
 
-    let term = term.as_ref().unwrap();
+impl FromStr for Output {
+    type Err = String;
 
-    if word {
-        for e in entry
-            .title
-            .to_lowercase()
-            .split(|c: char| !c.is_alphabetic())
-        {
-            if e == term {
-                return true;
-            }
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Output::Table),
+            "json" => Ok(Output::Json),
+            "ndjson" => Ok(Output::Ndjson),
+            _ => Err(format!("unknown output format \"{}\"", s)),
         }
-        return false;
     }
+}
 
-    entry.title.to_lowercase().contains(term)
+fn format_uptime(entry: &Entry) -> String {
+    match entry.uptime() {
+        Some(dur) => format!("{:02}:{:02}", dur.num_hours(), dur.num_minutes() % 60),
+        None => "".to_string(),
+    }
 }
 
-fn print(entry: Entry) {
+fn print(entry: Entry, show_score: bool, show_category: bool, show_tags: bool) {
+    if show_score {
+        print!("{:.2} | ", entry.score);
+    }
     print!("{} | ", entry.lang);
     print!("https://twitch.tv/{:<14} | ", entry.display_name);
     print!("{:>4} viewers | ", entry.viewer_count);
-    print!("{} | ", entry.live_duration);
+    print!("{} | ", format_uptime(&entry));
+    if show_category {
+        print!("{} | ", entry.game_name);
+    }
+    if show_tags {
+        print!("{} | ", entry.tags.join(", "));
+    }
+    if entry.is_mature {
+        print!("[18+] ");
+    }
     println!("{}", entry.title);
 }
 
-fn to_entry(value: &mut Value) -> Entry {
-    let value = value.take();
+// -----------------------------------------------------------------------------
+//     - Excluded terms -
+// -----------------------------------------------------------------------------
+fn exclusions(exclude: Option<Vec<String>>) -> Vec<String> {
+    let mut excluded = match exclude {
+        Some(exclusions) => exclusions.iter().map(|x| x.to_lowercase()).collect(),
+        None => vec![],
+    };
+
+    if let Ok(ignore_list) = env::var("TWITCH_IGNORE") {
+        excluded.extend(ignore_list.split(',').map(str::to_lowercase));
+    }
+
+    excluded
+}
+
+fn excluded_terms(exclude_term: Vec<String>) -> Vec<String> {
+    let mut terms = exclude_term;
 
-    Entry {
-        lang: to_str!(value, "language"),
-        display_name: to_str!(value, "user_name"),
-        title: to_str!(value, "title").replace("\n", "…"),
-        viewer_count: to_num!(value, "viewer_count"),
-        live_duration: to_instant(&to_str!(value, "started_at")),
+    if let Ok(ignore_list) = env::var("TWITCH_IGNORE_TERMS") {
+        terms.extend(ignore_list.split(',').map(str::to_string));
     }
+
+    terms
+        .iter()
+        .map(|term| term.trim().to_string())
+        .filter(|term| !term.is_empty())
+        .collect()
 }
 
-fn fetch(after: Option<String>) -> (Vec<Entry>, Option<String>) {
-    let url = match after {
-        Some(after) => format!("{}&after={}", ROOT_URL, after),
-        None => ROOT_URL.to_string(),
-    };
+// -----------------------------------------------------------------------------
+//     - Watched streamers -
+// -----------------------------------------------------------------------------
+fn watched(only: Vec<String>, only_file: Option<String>) -> Result<Vec<String>> {
+    let mut logins = only;
+
+    if let Some(path) = only_file {
+        let contents = fs::read_to_string(&path).map_err(|e| Error::Io {
+            path: path.clone(),
+            message: e.to_string(),
+        })?;
+        logins.extend(
+            contents
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or_default().to_string()),
+        );
+    }
 
-    let client_id = match env::var("TWITCH_CLIENT_ID") {
-        Ok(cid) => cid,
-        Err(_e) => {
-            eprintln!("Client id missing");
-            exit(1);
+    // Logins are case insensitive, and people tend to paste them as @name
+    let mut watched = Vec::new();
+    for login in logins {
+        let login = login.trim().trim_start_matches('@').to_lowercase();
+        if !login.is_empty() && !watched.contains(&login) {
+            watched.push(login);
         }
-    };
+    }
 
-    let token = match env::var("TWITCH_TOKEN") {
-        Ok(t) => t,
-        Err(_e) => {
-            eprintln!("OAuth token missing");
-            exit(1);
-        }
-    };
+    Ok(watched)
+}
+
+// -----------------------------------------------------------------------------
+//     - Mature content -
+// -----------------------------------------------------------------------------
+fn no_mature(no_mature: bool) -> bool {
+    match env::var("TWITCH_NO_MATURE") {
+        Ok(value) => no_mature || !matches!(value.trim(), "" | "0" | "false" | "no"),
+        Err(_) => no_mature,
+    }
+}
+
+fn excluded_labels(exclude_label: Vec<String>) -> Vec<String> {
+    let mut labels = exclude_label;
+
+    if let Ok(label_list) = env::var("TWITCH_EXCLUDE_LABELS") {
+        labels.extend(label_list.split(',').map(str::to_string));
+    }
+
+    labels
+        .iter()
+        .map(|label| label.trim().to_lowercase())
+        .filter(|label| !label.is_empty())
+        .collect()
+}
 
-    // -----------------------------------------------------------------------------
-    //     - Proxy -
-    // -----------------------------------------------------------------------------
-    let proxy = env::var("https_proxy")
-        .ok()
-        .and_then(|p| ureq::Proxy::new(p).ok());
-
-    let mut agent = ureq::AgentBuilder::new();
-    if let Some(proxy) = proxy {
-        agent = agent.proxy(proxy);
-    }
-    let agent = agent.build();
-
-    // -----------------------------------------------------------------------------
-    //     - Request -
-    // -----------------------------------------------------------------------------
-    let resp = agent
-        .get(&url)
-        .set("Authorization", &format!("Bearer {}", token))
-        .set("Client-Id", &client_id)
-        .call();
-
-    let mut json: Value = match resp.unwrap().into_json() {
-        Ok(j) => j,
-        Err(e) => {
-            eprintln!("failed to serialize json: {:?}", e);
-            exit(1);
+// -----------------------------------------------------------------------------
+//     - Main -
+// -----------------------------------------------------------------------------
+fn main() {
+    let args = Args::parse();
+    let term = args.term.clone();
+
+    if args.output == Output::Ndjson && (args.sort.is_some() || args.reverse) {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--output ndjson prints streams as they're found, so can't be used with --sort or --reverse",
+            )
+            .exit();
+    }
+
+    if let Err(e) = run(args) {
+        eprintln!("{}", e);
+        if let (Error::InvalidQuery { column, .. }, Some(term)) = (&e, term) {
+            eprintln!("  {}", term);
+            eprintln!("  {}^", " ".repeat(column - 1));
         }
-    };
+        exit(e.exit_code());
+    }
+}
 
-    let pagination = json
-        .get_mut("pagination")
-        .take()
-        .and_then(|v| v.get("cursor").take())
-        .and_then(|v| v.as_str())
-        .map(|v| v.to_string());
+fn run(args: Args) -> Result<()> {
+    let mut client = Client::from_env()?;
+    if let Some(api_base) = &args.api_base {
+        client = client.with_api_base(api_base);
+    }
 
-    let data = match json.get_mut("data") {
-        Some(Value::Array(a)) => a.iter_mut().map(to_entry).collect::<Vec<_>>(),
-        _ => exit(0),
+    let result = match args.command {
+        Some(Command::Auth {
+            command: AuthCommand::Status,
+        }) => auth_status(&client),
+        Some(Command::Tags) => run_tags(&client, args),
+        None => run_search(&client, args),
     };
 
-    (data, pagination)
+    // Piped into something like head that stopped reading, nothing to report
+    match result {
+        Err(Error::BrokenPipe) => Ok(()),
+        result => result,
+    }
+}
+
+fn humanize(dur: Duration) -> String {
+    if dur.num_days() > 0 {
+        format!("{}d {}h", dur.num_days(), dur.num_hours() % 24)
+    } else if dur.num_hours() > 0 {
+        format!("{}h {}m", dur.num_hours(), dur.num_minutes() % 60)
+    } else {
+        format!("{}m", dur.num_minutes())
+    }
 }
 
 // -----------------------------------------------------------------------------
-//     - Excluded terms -
+//     - Auth status -
 // -----------------------------------------------------------------------------
-fn exclusions(exclude: Option<Vec<String>>) -> Vec<String> {
-    let mut excluded = match exclude {
-        Some(exclusions) => exclusions.iter().map(|x| x.to_lowercase()).collect(),
-        None => vec![],
+fn auth_status(client: &Client) -> Result<()> {
+    let validation = client.validate()?;
+
+    let scopes = match validation.scopes.is_empty() {
+        true => "none".to_string(),
+        false => validation.scopes.join(", "),
+    };
+    let expires = match validation.expires_at {
+        Some(at) => format!(
+            "in {} ({})",
+            humanize(at - Utc::now()),
+            at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+        ),
+        None => "never".to_string(),
     };
 
-    if let Ok(ignore_list) = env::var("TWITCH_IGNORE") {
-        excluded.extend(ignore_list.split(',').map(str::to_lowercase));
+    println!("Client id: {}", validation.client_id);
+    match &validation.login {
+        Some(login) => println!("Login:     {}", login),
+        None => println!("Login:     none (app access token)"),
     }
+    println!("Scopes:    {}", scopes);
+    println!("Expires:   {}", expires);
 
-    excluded
+    Ok(())
+}
+
+fn print_stats(stats: &Stats, elapsed: StdDuration) {
+    let retries = stats
+        .retries
+        .iter()
+        .map(|(status, count)| format!("{} x{}", status, count))
+        .collect::<Vec<_>>();
+    let retries = match retries.is_empty() {
+        true => "no retries".to_string(),
+        false => format!("{} retries ({})", stats.total_retries(), retries.join(", ")),
+    };
+
+    eprintln!(
+        "{} requests in {:.1}s, {}, {} rate limit pauses, waited {:.1}s",
+        stats.requests,
+        elapsed.as_secs_f64(),
+        retries,
+        stats.pauses,
+        stats.waited.as_secs_f64()
+    );
 }
 
 // -----------------------------------------------------------------------------
-//     - Main -
+//     - Search -
 // -----------------------------------------------------------------------------
-fn main() {
-    let args = Args::parse();
-    let search_term = args.term;
-    let word_boundary = args.word;
+fn match_mode(args: &Args) -> MatchMode {
+    match (args.word, args.regex, args.fuzzy) {
+        (true, _, _) => MatchMode::Word,
+        (_, true, _) => MatchMode::Regex,
+        (_, _, true) => MatchMode::Fuzzy {
+            threshold: args.threshold,
+        },
+        _ => MatchMode::Substring,
+    }
+}
 
-    let exclude = exclusions(args.exclude);
+/// `excluded_terms` are the merged --exclude-term and TWITCH_IGNORE_TERMS
+fn build_query(args: &Args, excluded_terms: &[String]) -> Result<Query> {
+    let mode = match_mode(args);
+    // An empty term matches everything, like no term at all
+    let query = match args.term.as_deref().filter(|t| !t.trim().is_empty()) {
+        Some(term) => Some(Expr::parse(term, mode, args.case_sensitive)?),
+        None => None,
+    };
+    let excluded_terms = excluded_terms
+        .iter()
+        .map(|term| Matcher::new(term, mode, args.case_sensitive))
+        .collect::<Result<_>>()?;
+
+    let lowercase = |values: &[String]| values.iter().map(|v| v.to_lowercase()).collect();
+    Ok(Query {
+        categories: args.category.clone(),
+        only: watched(args.only.clone(), args.only_file.clone())?,
+        filter: Filter {
+            query,
+            ignored_names: exclusions(args.exclude.clone()),
+            excluded_terms,
+            languages: lowercase(&args.lang),
+            tags: lowercase(&args.tag),
+            any_tags: lowercase(&args.any_tag),
+            excluded_tags: lowercase(&args.exclude_tag),
+            no_mature: no_mature(args.no_mature),
+            excluded_labels: excluded_labels(args.exclude_label.clone()),
+            min_viewers: args.min_viewers,
+            max_viewers: args.max_viewers,
+            min_uptime: args.min_uptime,
+            max_uptime: args.max_uptime,
+        },
+        limit: args.limit,
+        // ndjson has no totals to count, so there's no point crawling on
+        fast: args.fast || args.output == Output::Ndjson,
+        sort: args.sort.unwrap_or_default(),
+        reverse: args.reverse,
+    })
+}
 
-    if let Some(term) = &search_term {
-        println!("Searching for \"{}\"", term);
+fn preflight(client: &Client) -> Result<()> {
+    if let Some(expires_at) = client.preflight()?.and_then(|v| v.expires_at) {
+        let remaining = expires_at - Utc::now();
+        if remaining < Duration::hours(EXPIRY_WARNING_HOURS) {
+            eprintln!("Warning: OAuth token expires in {}", humanize(remaining));
+        }
     }
+    Ok(())
+}
 
-    let mut total = 0;
-    let mut result = Vec::new();
+fn crawl<F>(client: &Client, query: &Query, verbose: bool, on_match: F) -> Result<SearchResult>
+where
+    F: FnMut(&Entry) -> Result<()>,
+{
+    let started = Instant::now();
+    let result = search_each(client, query, on_match);
+    if verbose {
+        print_stats(&client.stats(), started.elapsed());
+    }
+    result
+}
 
-    // Even if there's a limit in args.limit, we still fetch all entries
-    // so we can get the total count for the final line.
-    let mut page = None;
-    loop {
-        let (entries, p) = fetch(page);
-        total += entries.len();
-        page = p;
-        result.extend(entries);
-        if page.is_none() {
-            break;
-        }
+fn run_search(client: &Client, args: Args) -> Result<()> {
+    let excluded_terms = excluded_terms(args.exclude_term.clone());
+    let query = build_query(&args, &excluded_terms)?;
+    preflight(client)?;
+
+    if let (Output::Table, Some(term)) = (args.output, &args.term) {
+        println!("Searching for \"{}\"", term);
+    }
+
+    let started_at = Utc::now();
+    let streaming = args.output == Output::Ndjson;
+    let result = crawl(client, &query, args.verbose, |entry| match streaming {
+        true => print_ndjson(entry),
+        false => Ok(()),
+    })?;
+    match args.output {
+        Output::Table => print_table(&args, &query, result),
+        Output::Json => print_json(&args, &query, &excluded_terms, &result, started_at)?,
+        Output::Ndjson => {}
     }
 
-    let limit = if args.limit == 0 { total } else { args.limit };
-    let found = result
+    Ok(())
+}
+
+fn print_table(args: &Args, query: &Query, result: SearchResult) {
+    let found = result.entries.len();
+    let total = match result.complete {
+        true => result.total.to_string(),
+        false => format!("at least {}", result.total),
+    };
+    result
+        .entries
         .into_iter()
-        .filter(|e| filter(e, word_boundary, &search_term, &exclude))
-        .take(limit)
-        .map(print)
-        .count();
+        .for_each(|e| print(e, args.fuzzy, args.show_category, args.show_tags));
+
+    let watching = query.only.len();
+    if watching > 0 {
+        println!("Done ({found}/{total} live, {watching} watched)");
+    } else if result.categories.len() > 1 {
+        let per_category = result
+            .categories
+            .iter()
+            .map(|c| format!("{}: {}/{}", c.category, c.found, c.total))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("Done ({found}/{total}) [{per_category}]");
+    } else {
+        println!("Done ({found}/{total})");
+    }
+}
+
+// -----------------------------------------------------------------------------
+//     - Json -
+// -----------------------------------------------------------------------------
+fn print_json(
+    args: &Args,
+    query: &Query,
+    excluded_terms: &[String],
+    result: &SearchResult,
+    started_at: DateTime<Utc>,
+) -> Result<()> {
+    let terms = Terms {
+        term: args.term.as_deref(),
+        mode: match_mode(args),
+        case_sensitive: args.case_sensitive,
+        excluded_terms,
+    };
+    let document = json::document(terms, query, result, started_at);
+
+    let document =
+        serde_json::to_string_pretty(&document).map_err(|e| Error::Json(e.to_string()))?;
+    println!("{}", document);
+    Ok(())
+}
+
+fn print_ndjson(entry: &Entry) -> Result<()> {
+    let line =
+        serde_json::to_string(&Stream::from(entry)).map_err(|e| Error::Json(e.to_string()))?;
+
+    // Flushed per line so the next tool in the pipeline sees it right away
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", line)
+        .and_then(|_| stdout.flush())
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::BrokenPipe => Error::BrokenPipe,
+            _ => Error::Output(e.to_string()),
+        })
+}
+
+// -----------------------------------------------------------------------------
+//     - Tags -
+// -----------------------------------------------------------------------------
+fn run_tags(client: &Client, args: Args) -> Result<()> {
+    // Every matching stream counts, --limit applies to the tags instead
+    let query = Query {
+        limit: 0,
+        fast: false,
+        ..build_query(&args, &excluded_terms(args.exclude_term.clone()))?
+    };
+    preflight(client)?;
+
+    let result = crawl(client, &query, args.verbose, |_| Ok(()))?;
+    let mut tags = popular_tags(&result.entries);
+    let count = tags.len();
+    if args.limit > 0 {
+        tags.truncate(args.limit);
+    }
+
+    let width = tags
+        .iter()
+        .map(|t| t.tag.chars().count())
+        .max()
+        .unwrap_or_default()
+        .max("Tag".len());
+    println!("{:<width$} | Streams | Viewers", "Tag");
+    for tag in tags {
+        println!(
+            "{:<width$} | {:>7} | {:>7}",
+            tag.tag, tag.streams, tag.viewers
+        );
+    }
+
+    println!(
+        "Done ({} tags across {}/{} streams)",
+        count,
+        result.entries.len(),
+        result.total
+    );
 
-    println!("Done ({found}/{total})");
+    Ok(())
 }
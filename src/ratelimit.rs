@@ -0,0 +1,104 @@
+// -----------------------------------------------------------------------------
+//     - Rate limiting -
+//     https://dev.twitch.tv/docs/api/guide/#twitch-rate-limits
+//
+//     Helix hands out a bucket of points that refills every minute and
+//     reports its state on every response. We back off before the bucket
+//     runs dry, and retry the requests that failed anyway.
+// -----------------------------------------------------------------------------
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use chrono::prelude::*;
+
+use crate::transport::Response;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimit {
+    pub limit: u32,
+    pub remaining: u32,
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// Read the `Ratelimit-*` headers, if the response has all of them
+    pub fn from_response(resp: &Response) -> Option<Self> {
+        let limit = resp.header("Ratelimit-Limit")?.parse().ok()?;
+        let remaining = resp.header("Ratelimit-Remaining")?.parse().ok()?;
+        let reset = resp.header("Ratelimit-Reset")?.parse().ok()?;
+        let reset = Utc.timestamp_opt(reset, 0).single()?;
+
+        Some(Self {
+            limit,
+            remaining,
+            reset,
+        })
+    }
+
+    /// Time until the bucket refills
+    pub fn until_reset(&self) -> Duration {
+        (self.reset - Utc::now()).to_std().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// How many times a 429 or a transient 5xx is retried
+    pub max_retries: u32,
+    /// First back-off delay, doubled on every retry
+    pub base_delay: Duration,
+    /// Upper bound for any single wait, back-off or rate limit pause
+    pub max_delay: Duration,
+    /// Pause until the bucket refills once this few points are left
+    pub min_remaining: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(60),
+            min_remaining: 2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn should_retry(&self, status: u16, attempt: u32) -> bool {
+        attempt < self.max_retries && matches!(status, 429 | 500 | 502 | 503 | 504)
+    }
+
+    /// Exponential back-off with jitter, so a burst of failed requests
+    /// doesn't come back all at once
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+
+        // Somewhere between half and all of the delay
+        let jitter = RandomState::new().build_hasher().finish() % 1000;
+        delay / 2 + delay / 2 * jitter as u32 / 1000
+    }
+}
+
+/// What it took to get through a crawl
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub requests: u32,
+    /// Retries, by the status that caused them
+    pub retries: BTreeMap<u16, u32>,
+    /// Times we stopped to let the bucket refill
+    pub pauses: u32,
+    /// Total time spent in back-off and pauses
+    pub waited: Duration,
+}
+
+impl Stats {
+    pub fn total_retries(&self) -> u32 {
+        self.retries.values().sum()
+    }
+}
@@ -0,0 +1,265 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::client::{Client, StreamParams};
+use crate::entry::Entry;
+use crate::error::Result;
+use crate::filter::Filter;
+use crate::DEFAULT_CATEGORY;
+
+/// Order of the results. Numbers sort biggest first, text A to Z.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sort {
+    /// Best match first. Only fuzzy matches have a score, so for anything
+    /// else this keeps the order Twitch returned them in.
+    #[default]
+    Relevance,
+    Viewers,
+    Uptime,
+    Name,
+    Lang,
+}
+
+impl Sort {
+    pub const NAMES: &'static [&'static str] = &["relevance", "viewers", "uptime", "name", "lang"];
+
+    fn compare(self, a: &Entry, b: &Entry) -> Ordering {
+        match self {
+            Sort::Relevance => b.score.total_cmp(&a.score),
+            Sort::Viewers => b.viewer_count.cmp(&a.viewer_count),
+            // Earliest start first, unknown start times last
+            Sort::Uptime => {
+                let (a, b) = (a.started_at, b.started_at);
+                a.is_none().cmp(&b.is_none()).then(a.cmp(&b))
+            }
+            Sort::Name => a
+                .display_name
+                .to_lowercase()
+                .cmp(&b.display_name.to_lowercase()),
+            Sort::Lang => a.lang.cmp(&b.lang),
+        }
+    }
+}
+
+impl FromStr for Sort {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "relevance" => Ok(Sort::Relevance),
+            "viewers" => Ok(Sort::Viewers),
+            "uptime" => Ok(Sort::Uptime),
+            "name" => Ok(Sort::Name),
+            "lang" => Ok(Sort::Lang),
+            _ => Err(format!("unknown sort order \"{}\"", s)),
+        }
+    }
+}
+
+/// What to search for and where
+#[derive(Debug, Clone)]
+pub struct Query {
+    /// Game ids or category names
+    pub categories: Vec<String>,
+    /// Logins to look up directly, in which case the categories are
+    /// ignored and nothing is crawled
+    pub only: Vec<String>,
+    pub filter: Filter,
+    /// Maximum number of entries to return, 0 means all
+    pub limit: usize,
+    /// Stop crawling as soon as `limit` entries are found, rather than
+    /// fetching every page to get exact totals. Only what was found by
+    /// then is sorted.
+    pub fast: bool,
+    /// Applied before the limit, so the limit keeps the top entries
+    pub sort: Sort,
+    /// Flip the whole order, ties included, so reversing relevance without
+    /// fuzzy scores flips the order Twitch returned
+    pub reverse: bool,
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Self {
+            categories: vec![DEFAULT_CATEGORY.to_string()],
+            only: Vec::new(),
+            filter: Filter::default(),
+            limit: 0,
+            fast: false,
+            sort: Sort::default(),
+            reverse: false,
+        }
+    }
+}
+
+/// How many streams a category had, and how many of them matched
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryTotal {
+    /// The category as given in the query
+    pub category: String,
+    pub total: usize,
+    pub found: usize,
+}
+
+#[derive(Debug)]
+pub struct SearchResult {
+    pub entries: Vec<Entry>,
+    pub total: usize,
+    pub categories: Vec<CategoryTotal>,
+    /// Pages fetched from Twitch
+    pub pages: usize,
+    /// False if a fast search stopped early, the totals are then only
+    /// what was seen before stopping
+    pub complete: bool,
+}
+
+/// Most languages a single `/streams` request can ask for
+const MAX_LANGUAGES: usize = 100;
+
+/// Most logins a single `/streams` request can ask for
+const MAX_LOGINS: usize = 100;
+
+/// Where the streams come from
+enum Source<'a> {
+    /// A category, and its index in the query
+    Category(usize, &'a str),
+    /// A batch of logins
+    Logins(&'a [String]),
+}
+
+/// Crawl every page of every category in the query and return the matches
+pub fn search(client: &Client, query: &Query) -> Result<SearchResult> {
+    search_each(client, query, |_| Ok(()))
+}
+
+/// Like [`search`], also handing each match to `on_match` as soon as its
+/// page is processed. That's in the order they were found, before sorting,
+/// and at most `limit` of them. The crawl itself only stops early for a
+/// `fast` query. An error from `on_match` ends the search.
+pub fn search_each<F>(client: &Client, query: &Query, mut on_match: F) -> Result<SearchResult>
+where
+    F: FnMut(&Entry) -> Result<()>,
+{
+    let limit = if query.limit == 0 {
+        usize::MAX
+    } else {
+        query.limit
+    };
+
+    let mut categories = match query.only.is_empty() {
+        true => query
+            .categories
+            .iter()
+            .map(|category| CategoryTotal {
+                category: category.clone(),
+                total: 0,
+                found: 0,
+            })
+            .collect(),
+        false => Vec::new(),
+    };
+    let sources = match query.only.is_empty() {
+        true => query
+            .categories
+            .iter()
+            .enumerate()
+            .map(|(index, category)| Source::Category(index, category))
+            .collect::<Vec<_>>(),
+        false => query.only.chunks(MAX_LOGINS).map(Source::Logins).collect(),
+    };
+
+    // Let Twitch filter by language when it can, so there are fewer
+    // pages to fetch. The filter checks again either way.
+    let languages = match query.filter.languages.len() {
+        0..=MAX_LANGUAGES => query.filter.languages.clone(),
+        _ => Vec::new(),
+    };
+
+    let mut total = 0;
+    let mut fetched = 0;
+    let mut seen = HashSet::new();
+    let mut matches = Vec::new();
+    let mut complete = true;
+
+    // Unless it's a fast search, we fetch all entries even if there's a
+    // limit so we can get the total count.
+    'crawl: for (n, source) in sources.iter().enumerate() {
+        let (index, params) = match source {
+            Source::Category(index, category) => {
+                let params = StreamParams {
+                    game_id: Some(client.resolve_category(category)?),
+                    languages: languages.clone(),
+                    ..StreamParams::default()
+                };
+                (Some(*index), params)
+            }
+            Source::Logins(logins) => {
+                let params = StreamParams {
+                    user_logins: logins.to_vec(),
+                    languages: languages.clone(),
+                    ..StreamParams::default()
+                };
+                (None, params)
+            }
+        };
+
+        let mut pages = client.pages(params);
+        for page in pages.by_ref() {
+            let page = page?;
+            fetched += 1;
+            total += page.entries.len();
+            if let Some(index) = index {
+                categories[index].total += page.entries.len();
+            }
+
+            // The same stream can show up more than once, either because
+            // it moved between pages or the category was given twice.
+            for entry in page.entries {
+                if !seen.insert(entry.user_id.clone()) {
+                    continue;
+                }
+                if let Some(score) = query.filter.score(&entry) {
+                    let entry = Entry { score, ..entry };
+                    if matches.len() < limit {
+                        on_match(&entry)?;
+                    }
+                    matches.push((index, entry));
+                }
+            }
+
+            if query.fast && matches.len() >= limit {
+                complete = pages.is_exhausted() && n + 1 == sources.len();
+                break 'crawl;
+            }
+        }
+    }
+
+    // Stable, so ties stay in the order Twitch returned them
+    matches.sort_by(|(_, a), (_, b)| query.sort.compare(a, b));
+    if query.reverse {
+        matches.reverse();
+    }
+    matches.truncate(limit);
+
+    let entries = matches
+        .into_iter()
+        .map(|(index, entry)| {
+            if let Some(index) = index {
+                categories[index].found += 1;
+            }
+            entry
+        })
+        .collect();
+
+    Ok(SearchResult {
+        entries,
+        total,
+        categories,
+        pages: fetched,
+        complete,
+    })
+}
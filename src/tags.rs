@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use crate::entry::Entry;
+
+/// How often a tag is used and how many people are watching it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagCount {
+    /// The tag as first seen, tags are compared case insensitively
+    pub tag: String,
+    pub streams: usize,
+    pub viewers: i64,
+}
+
+/// Count the tags across the entries, most used first, ties broken by
+/// viewers and then by name
+pub fn popular_tags(entries: &[Entry]) -> Vec<TagCount> {
+    let mut counts: Vec<TagCount> = Vec::new();
+    let mut index = HashMap::new();
+
+    for entry in entries {
+        // Count each tag once per stream, even if it's listed twice
+        let mut seen = Vec::new();
+        for tag in &entry.tags {
+            let key = tag.to_lowercase();
+            if seen.contains(&key) {
+                continue;
+            }
+            seen.push(key.clone());
+
+            let i = *index.entry(key).or_insert_with(|| {
+                counts.push(TagCount {
+                    tag: tag.clone(),
+                    streams: 0,
+                    viewers: 0,
+                });
+                counts.len() - 1
+            });
+            counts[i].streams += 1;
+            counts[i].viewers += entry.viewer_count;
+        }
+    }
+
+    counts.sort_by(|a, b| {
+        b.streams
+            .cmp(&a.streams)
+            .then(b.viewers.cmp(&a.viewers))
+            .then_with(|| a.tag.to_lowercase().cmp(&b.tag.to_lowercase()))
+    });
+    counts
+}
@@ -0,0 +1,146 @@
+// -----------------------------------------------------------------------------
+//     - Transport -
+//     The http layer underneath the client. Swap it out to talk to
+//     something other than the real api, e.g. a canned mock in tests.
+// -----------------------------------------------------------------------------
+use std::env;
+
+use url::form_urlencoded;
+use url::Url;
+
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: &'static str,
+    pub url: Url,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl Request {
+    pub fn get(url: Url) -> Self {
+        Self {
+            method: "GET",
+            url,
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    /// A POST with an `application/x-www-form-urlencoded` body
+    pub fn post_form(url: Url, params: &[(&str, &str)]) -> Self {
+        let body = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params)
+            .finish();
+
+        Self {
+            method: "POST",
+            url,
+            headers: Vec::new(),
+            body: Some(body),
+        }
+        .header("Content-Type", "application/x-www-form-urlencoded")
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Any response that made it back, whatever the status
+#[derive(Debug, Clone, Default)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    /// Header lookup, ignoring case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Send + Sync so a [`Client`](crate::Client) can be moved to another thread
+pub trait Transport: Send + Sync {
+    /// Send the request. Only failing to get a response at all is an
+    /// error, non-success statuses are left for the caller to interpret.
+    fn send(&self, request: &Request) -> Result<Response>;
+}
+
+/// The default transport, honouring `https_proxy`. Holds on to one agent
+/// so connections are pooled rather than set up again for every page.
+#[derive(Debug)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+
+impl UreqTransport {
+    pub fn new() -> Self {
+        // -----------------------------------------------------------------------------
+        //     - Proxy -
+        // -----------------------------------------------------------------------------
+        let proxy = env::var("https_proxy")
+            .ok()
+            .and_then(|p| ureq::Proxy::new(p).ok());
+
+        let mut agent = ureq::AgentBuilder::new();
+        if let Some(proxy) = proxy {
+            agent = agent.proxy(proxy);
+        }
+
+        Self {
+            agent: agent.build(),
+        }
+    }
+}
+
+impl Default for UreqTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for UreqTransport {
+    fn send(&self, request: &Request) -> Result<Response> {
+        let mut req = self.agent.request_url(request.method, &request.url);
+        for (name, value) in &request.headers {
+            req = req.set(name, value);
+        }
+
+        let resp = match &request.body {
+            Some(body) => req.send_string(body),
+            None => req.call(),
+        };
+        let resp = match resp {
+            Ok(resp) => resp,
+            Err(ureq::Error::Status(_, resp)) => resp,
+            Err(ureq::Error::Transport(t)) => return Err(Error::Transport(t.to_string())),
+        };
+
+        let status = resp.status();
+        let headers = resp
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = resp.header(&name)?.to_string();
+                Some((name, value))
+            })
+            .collect();
+        let body = resp
+            .into_string()
+            .map_err(|e| Error::Transport(e.to_string()))?;
+
+        Ok(Response {
+            status,
+            headers,
+            body,
+        })
+    }
+}
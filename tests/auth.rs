@@ -0,0 +1,228 @@
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chrono::{Duration, Utc};
+use common::{page, stream, Recorded, Reply, Stub};
+use serde_json::json;
+use twitch_search::auth::Token;
+use twitch_search::{search, Client, Error, Query};
+
+fn cache_path(name: &str) -> PathBuf {
+    let path = env::temp_dir()
+        .join(format!("twitch-search-test-{}", std::process::id()))
+        .join(format!("{}.json", name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// Hands out token-1, token-2... and only accepts the most recent one
+fn token_server() -> impl Fn(&Recorded) -> Reply + Send + 'static {
+    let issued = AtomicUsize::new(0);
+    move |request| match request.path.as_str() {
+        "/oauth2/token" => {
+            assert_eq!(request.method, "POST");
+            assert!(request.body.contains("grant_type=client_credentials"));
+            assert!(request.body.contains("client_secret=secret"));
+            let n = issued.fetch_add(1, Ordering::SeqCst) + 1;
+            Reply::json(json!({
+                "access_token": format!("token-{}", n),
+                "expires_in": 3600,
+                "token_type": "bearer",
+            }))
+        }
+        _ => {
+            let current = format!("Bearer token-{}", issued.load(Ordering::SeqCst));
+            if request.headers["authorization"] == current {
+                Reply::json(page(vec![stream("alice", "rust", 1)], None))
+            } else {
+                Reply::status(
+                    401,
+                    json!({ "status": 401, "message": "Invalid OAuth token" }),
+                )
+            }
+        }
+    }
+}
+
+fn client(stub: &Stub, cache: &PathBuf) -> Client {
+    Client::from_secret("client-id", "secret")
+        .with_api_base(format!("{}/helix", stub.base))
+        .with_auth_base(format!("{}/oauth2", stub.base))
+        .with_token_cache(cache)
+}
+
+#[test]
+fn fetches_and_caches_an_app_token() {
+    let stub = Stub::start(token_server());
+    let cache = cache_path("fetches");
+
+    search(&client(&stub, &cache), &Query::default()).unwrap();
+
+    assert_eq!(stub.requests_to("/oauth2/token").len(), 1);
+    let cached = Token::load(&cache, "client-id").unwrap();
+    assert_eq!(cached.access_token, "token-1");
+    assert!(cached.expires_at > Utc::now() + Duration::minutes(59));
+
+    // A second run picks the token up from disk
+    search(&client(&stub, &cache), &Query::default()).unwrap();
+    assert_eq!(stub.requests_to("/oauth2/token").len(), 1);
+}
+
+#[test]
+fn ignores_expired_or_foreign_cached_tokens() {
+    let stub = Stub::start(token_server());
+    let cache = cache_path("expired");
+
+    Token {
+        client_id: "client-id".to_string(),
+        access_token: "stale".to_string(),
+        expires_at: Utc::now() - Duration::minutes(1),
+    }
+    .save(&cache);
+    assert!(Token::load(&cache, "client-id").is_none());
+
+    Token {
+        client_id: "someone-else".to_string(),
+        access_token: "theirs".to_string(),
+        expires_at: Utc::now() + Duration::hours(1),
+    }
+    .save(&cache);
+    assert!(Token::load(&cache, "client-id").is_none());
+
+    search(&client(&stub, &cache), &Query::default()).unwrap();
+    assert_eq!(stub.requests_to("/oauth2/token").len(), 1);
+}
+
+#[test]
+fn refreshes_a_revoked_token_and_retries() {
+    let stub = Stub::start(token_server());
+    let cache = cache_path("revoked");
+
+    Token {
+        client_id: "client-id".to_string(),
+        access_token: "revoked".to_string(),
+        expires_at: Utc::now() + Duration::hours(1),
+    }
+    .save(&cache);
+
+    let result = search(&client(&stub, &cache), &Query::default()).unwrap();
+
+    assert_eq!(result.entries.len(), 1);
+    assert_eq!(stub.requests_to("/helix/streams").len(), 2);
+    assert_eq!(
+        Token::load(&cache, "client-id").unwrap().access_token,
+        "token-1"
+    );
+}
+
+#[test]
+fn rejected_secret() {
+    let stub = Stub::start(|_| {
+        Reply::status(
+            403,
+            json!({ "status": 403, "message": "invalid client secret" }),
+        )
+    });
+    let cache = cache_path("rejected");
+
+    match search(&client(&stub, &cache), &Query::default()) {
+        Err(Error::Http { status, message }) => {
+            assert_eq!(status, 403);
+            assert_eq!(message, "invalid client secret");
+        }
+        other => panic!("expected http error, got {:?}", other.map(|r| r.total)),
+    }
+}
+
+// -----------------------------------------------------------------------------
+//     - Validation -
+// -----------------------------------------------------------------------------
+fn validating(stub: &Stub, cache: &PathBuf) -> Client {
+    Client::new("client-id", "user-token")
+        .with_auth_base(format!("{}/oauth2", stub.base))
+        .with_validation_cache(cache)
+}
+
+fn valid_for(expires_in: i64) -> impl Fn(&Recorded) -> Reply + Send + 'static {
+    move |request| {
+        assert_eq!(request.path, "/oauth2/validate");
+        assert_eq!(request.headers["authorization"], "OAuth user-token");
+        Reply::json(json!({
+            "client_id": "client-id",
+            "login": "alice",
+            "user_id": "1234",
+            "scopes": ["user:read:email"],
+            "expires_in": expires_in,
+        }))
+    }
+}
+
+#[test]
+fn validate_reports_the_token() {
+    let stub = Stub::start(valid_for(3600));
+    let cache = cache_path("validate");
+
+    let validation = validating(&stub, &cache).validate().unwrap();
+
+    assert_eq!(validation.client_id, "client-id");
+    assert_eq!(validation.login.as_deref(), Some("alice"));
+    assert_eq!(validation.scopes, ["user:read:email"]);
+    assert!(validation.expires_at.unwrap() > Utc::now() + Duration::minutes(59));
+}
+
+#[test]
+fn preflight_is_cached() {
+    let stub = Stub::start(valid_for(0));
+    let cache = cache_path("preflight");
+
+    let validation = validating(&stub, &cache).preflight().unwrap().unwrap();
+    assert_eq!(validation.expires_at, None);
+
+    validating(&stub, &cache).preflight().unwrap().unwrap();
+    assert_eq!(stub.requests().len(), 1);
+}
+
+#[test]
+fn preflight_reports_when_the_token_expired() {
+    let stub = Stub::start(valid_for(1));
+    let cache = cache_path("preflight-expired");
+
+    validating(&stub, &cache).preflight().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+
+    // Known to have expired from the cache alone, no need to ask again
+    match validating(&stub, &cache).preflight() {
+        Err(e @ Error::TokenExpired(Some(_))) => assert!(e.to_string().contains("expired at")),
+        other => panic!("expected expired token, got {:?}", other),
+    }
+    assert_eq!(stub.requests().len(), 1);
+}
+
+#[test]
+fn preflight_rejected_token() {
+    let stub = Stub::start(|_| {
+        Reply::status(
+            401,
+            json!({ "status": 401, "message": "invalid access token" }),
+        )
+    });
+    let cache = cache_path("preflight-rejected");
+
+    assert!(matches!(
+        validating(&stub, &cache).preflight(),
+        Err(Error::TokenExpired(None))
+    ));
+}
+
+#[test]
+fn preflight_skipped_for_app_tokens() {
+    let stub = Stub::start(token_server());
+    let cache = cache_path("preflight-app");
+
+    assert!(client(&stub, &cache).preflight().unwrap().is_none());
+    assert!(stub.requests().is_empty());
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use std::env;
+use std::io::Read;
+use std::process::{Command, Stdio};
+
+use common::{page, stream, Reply, Stub};
+use serde_json::json;
+
+#[test]
+fn closed_pipe_is_a_clean_exit() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/oauth2/validate" => Reply::json(json!({
+            "client_id": "client-id",
+            "scopes": [],
+            "expires_in": 3600 * 24 * 30,
+        })),
+        _ => {
+            let streams = (0..100)
+                .map(|n| stream(&format!("user{}", n), "rust", n))
+                .collect();
+            Reply::json(page(streams, None))
+        }
+    });
+    let cache = env::temp_dir().join(format!("twitch-search-cli-{}", std::process::id()));
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_twitch-search"))
+        .args(["--output", "ndjson", "rust"])
+        .env_clear()
+        .env("TWITCH_CLIENT_ID", "client-id")
+        .env("TWITCH_TOKEN", "token")
+        .env("TWITCH_API_BASE", format!("{}/helix", stub.base))
+        .env("TWITCH_AUTH_BASE", format!("{}/oauth2", stub.base))
+        .env("XDG_CACHE_HOME", &cache)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Like `| head -0`, the reader is gone before anything is written
+    drop(child.stdout.take());
+    let mut stderr = String::new();
+    child
+        .stderr
+        .take()
+        .unwrap()
+        .read_to_string(&mut stderr)
+        .unwrap();
+    let status = child.wait().unwrap();
+
+    assert_eq!(stderr, "");
+    assert_eq!(status.code(), Some(0));
+    assert_eq!(stub.requests_to("/helix/streams").len(), 1);
+}
@@ -0,0 +1,242 @@
+// -----------------------------------------------------------------------------
+//     - Stub server -
+//     A tiny http server on localhost that answers with canned Helix json,
+//     so the real transport can be exercised without touching Twitch.
+// -----------------------------------------------------------------------------
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde_json::{json, Value};
+use twitch_search::{Client, Entry};
+use url::Url;
+
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl Recorded {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn params(&self, name: &str) -> Vec<&str> {
+        self.query
+            .iter()
+            .filter(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Reply {
+    pub fn json(body: Value) -> Self {
+        Self::status(200, body)
+    }
+
+    pub fn status(status: u16, body: Value) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.to_string(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+pub struct Stub {
+    pub base: String,
+    requests: Arc<Mutex<Vec<Recorded>>>,
+}
+
+impl Stub {
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&Recorded) -> Reply + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let request = match read_request(&mut stream) {
+                    Some(request) => request,
+                    None => continue,
+                };
+                let reply = handler(&request);
+                recorded.lock().unwrap().push(request);
+                write_reply(&mut stream, &reply);
+            }
+        });
+
+        Self { base, requests }
+    }
+
+    /// Everything the stub has been asked so far
+    pub fn requests(&self) -> Vec<Recorded> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn requests_to(&self, path: &str) -> Vec<Recorded> {
+        self.requests()
+            .into_iter()
+            .filter(|r| r.path == path)
+            .collect()
+    }
+
+    pub fn client(&self) -> Client {
+        Client::new("client-id", "token").with_api_base(format!("{}/helix", self.base))
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> Option<Recorded> {
+    let mut reader = BufReader::new(stream);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?.to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|l| l.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+
+    let url = Url::parse(&format!("http://stub{}", target)).ok()?;
+    Some(Recorded {
+        method,
+        path: url.path().to_string(),
+        query: url.query_pairs().into_owned().collect(),
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+fn write_reply(stream: &mut TcpStream, reply: &Reply) {
+    let mut head = format!(
+        "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status,
+        reply.body.len()
+    );
+    for (name, value) in &reply.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(reply.body.as_bytes());
+}
+
+// -----------------------------------------------------------------------------
+//     - Canned json -
+// -----------------------------------------------------------------------------
+pub fn stream(user: &str, title: &str, viewers: i64) -> Value {
+    json!({
+        "id": format!("{}-stream", user),
+        "user_id": format!("{}-id", user),
+        "user_login": user.to_lowercase(),
+        "user_name": user,
+        "game_id": "1469308723",
+        "game_name": "Science & Technology",
+        "type": "live",
+        "title": title,
+        "tags": [],
+        "viewer_count": viewers,
+        "started_at": "2021-03-10T15:04:21Z",
+        "language": "en",
+        "thumbnail_url": "",
+        "is_mature": false,
+    })
+}
+
+pub fn page(streams: Vec<Value>, cursor: Option<&str>) -> Value {
+    let pagination = match cursor {
+        Some(cursor) => json!({ "cursor": cursor }),
+        None => json!({}),
+    };
+    json!({ "data": streams, "pagination": pagination })
+}
+
+/// Serve `pages` one after another for `/helix/streams`, chained by cursor
+pub fn paginated(pages: Vec<Vec<Value>>) -> impl Fn(&Recorded) -> Reply + Send + 'static {
+    move |request| {
+        let index = request
+            .param("after")
+            .and_then(|a| a.strip_prefix("page-"))
+            .and_then(|a| a.parse::<usize>().ok())
+            .unwrap_or(0);
+        let cursor = format!("page-{}", index + 1);
+        let cursor = if index + 1 < pages.len() {
+            Some(cursor.as_str())
+        } else {
+            None
+        };
+        Reply::json(page(pages[index].clone(), cursor))
+    }
+}
+
+// -----------------------------------------------------------------------------
+//     - Entries -
+// -----------------------------------------------------------------------------
+/// A stream by alice with the given title, override fields with `..entry()`
+pub fn entry(title: &str) -> Entry {
+    Entry {
+        user_id: "1".to_string(),
+        login: "alice".to_string(),
+        lang: "en".to_string(),
+        display_name: "Alice".to_string(),
+        game_name: "Science & Technology".to_string(),
+        title: title.to_string(),
+        viewer_count: 1,
+        started_at: None,
+        tags: Vec::new(),
+        is_mature: false,
+        labels: Vec::new(),
+        score: 1.0,
+    }
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use twitch_search::{Entry, Error, Expr, MatchMode};
+
+fn entry(title: &str) -> Entry {
+    Entry {
+        tags: vec!["Rust".to_string(), "English".to_string()],
+        ..common::entry(title)
+    }
+}
+
+fn matches(query: &str, title: &str) -> bool {
+    Expr::parse(query, MatchMode::Substring, false)
+        .unwrap()
+        .matches(&entry(title))
+}
+
+fn error(query: &str) -> (usize, String) {
+    match Expr::parse(query, MatchMode::Substring, false) {
+        Err(Error::InvalidQuery { column, message }) => (column, message),
+        other => panic!("expected a parse error for {:?}, got {:?}", query, other),
+    }
+}
+
+#[test]
+fn boolean_operators() {
+    let query = "rust AND (bevy OR wgpu) NOT giveaway";
+    assert!(matches(query, "Rust + Bevy game jam"));
+    assert!(matches(query, "rust wgpu renderer"));
+    assert!(!matches(query, "rust bevy GIVEAWAY"));
+    assert!(!matches(query, "rust only"));
+    assert!(!matches(query, "bevy without the r-word"));
+}
+
+#[test]
+fn precedence() {
+    // AND binds tighter than OR
+    assert!(matches("go OR rust AND bevy", "go"));
+    assert!(!matches("go OR rust AND bevy", "rust"));
+    assert!(matches("NOT go rust", "rust"));
+    assert!(!matches("NOT (go OR rust)", "rust"));
+}
+
+#[test]
+fn phrases() {
+    assert!(matches("\"game dev\"", "late night game dev"));
+    assert!(!matches("\"game dev\"", "game of the year dev"));
+    assert!(matches("title:\"game dev\" rust", "rust game dev"));
+}
+
+#[test]
+fn lower_case_operators_are_terms() {
+    assert!(matches("rock and roll", "rock and roll"));
+    assert!(!matches("rock and roll", "rock roll"));
+}
+
+#[test]
+fn fields() {
+    assert!(matches("lang:en", "anything"));
+    assert!(!matches("lang:de", "anything"));
+    assert!(matches("user:ALICE", "anything"));
+    assert!(matches("tag:rust", "anything"));
+    assert!(!matches("tag:bevy", "anything"));
+    assert!(matches("tag:rust NOT lang:de", "anything"));
+    // Not a field, just a term with a colon in it
+    assert!(matches("12:00", "starting at 12:00"));
+    assert!(matches("re:zero", "watching Re:Zero"));
+    assert!(matches(
+        "https://github.com",
+        "code on https://github.com/me"
+    ));
+    assert!(matches("note:", "note: rust"));
+    assert!(!matches("rust game:dev", "rust game dev"));
+}
+
+#[test]
+fn parse_errors_point_at_the_column() {
+    assert_eq!(error("rust AND (bevy"), (15, "expected \")\"".to_string()));
+    assert_eq!(error("rust)"), (5, "unmatched \")\"".to_string()));
+    assert_eq!(error("rust AND"), (9, "expected a term".to_string()));
+    assert_eq!(
+        error("OR rust"),
+        (1, "expected a term, found OR".to_string())
+    );
+    assert_eq!(error("rust \"game"), (6, "unterminated quote".to_string()));
+    assert_eq!(
+        error("lang: en"),
+        (6, "expected a value after \"lang:\"".to_string())
+    );
+    assert_eq!(error(""), (1, "expected a term".to_string()));
+}
+
+#[test]
+fn columns_count_characters() {
+    assert_eq!(error("ürüst )"), (7, "unmatched \")\"".to_string()));
+}
+
+#[test]
+fn regex_is_one_pattern() {
+    let matches = |pattern: &str, title: &str| {
+        Expr::parse(pattern, MatchMode::Regex, false)
+            .unwrap()
+            .matches(&entry(title))
+    };
+
+    assert!(matches("^(rust|go) stream$", "Rust stream"));
+    assert!(!matches(
+        "^(rust|go) stream$",
+        "playing go, then a rust stream"
+    ));
+    assert!(matches("rust (2024)?", "rust 2024 edition"));
+    assert!(matches("\"rust\" AND", "the \"rust\" AND go show"));
+}
@@ -0,0 +1,254 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use twitch_search::filter::{parse_duration, parse_threshold};
+use twitch_search::{Entry, Error, Filter, MatchMode, Matcher, DEFAULT_THRESHOLD};
+
+fn matcher(term: &str, mode: MatchMode) -> Matcher {
+    Matcher::new(term, mode, false).unwrap()
+}
+
+#[test]
+fn substring_ignores_case() {
+    let m = matcher("Rust", MatchMode::Substring);
+    assert!(m.is_match("learning rust today"));
+    assert!(m.is_match("TRUSTED"));
+
+    let m = Matcher::new("Rust", MatchMode::Substring, true).unwrap();
+    assert!(!m.is_match("learning rust today"));
+    assert!(m.is_match("learning Rust today"));
+}
+
+#[test]
+fn regex() {
+    let m = matcher(r"\b(bevy|wgpu)\b", MatchMode::Regex);
+    assert!(m.is_match("Bevy jam day 3"));
+    assert!(m.is_match("writing a WGPU renderer"));
+    assert!(!m.is_match("bevvy"));
+
+    let m = Matcher::new(r"^Rust", MatchMode::Regex, true).unwrap();
+    assert!(m.is_match("Rust all day"));
+    assert!(!m.is_match("rust all day"));
+}
+
+#[test]
+fn invalid_regex() {
+    match Matcher::new("ru(st", MatchMode::Regex, false) {
+        Err(e @ Error::InvalidPattern(_)) => assert!(e.to_string().contains("unclosed group")),
+        other => panic!("expected invalid pattern, got {:?}", other),
+    }
+}
+
+#[test]
+fn words() {
+    #[rustfmt::skip]
+    let table = [
+        // term          title                          matches
+        ("rust",         "Rust gamedev",                true),
+        ("Rust",         "learning rust today",         true),
+        ("RUST",         "RuSt",                        true),
+        ("rust",         "trusty old code",             false),
+        ("rust",         "rust2024 edition",            false),
+        ("rust2024",     "Rust2024 edition",            true),
+        ("rust",         "rust-lang compiler",          true),
+        ("rust",         "rust_lang",                   false),
+        ("c++",          "C++ and Rust",                true),
+        ("c#",           "C++ and Rust",                false),
+        ("gamedev",      "gamedev's corner",            true),
+        ("gamedev",      "GAMEDEV’S corner",            true),
+        ("gamedev's",    "gamedev corner",              true),
+        ("game dev",     "late night game dev",         true),
+        ("game dev",     "game of the year dev",        false),
+        ("rust",         "🦀rust🦀 crab time",           true),
+        ("école",        "ÉCOLE de Rust",               true),
+        ("编程",          "今天编程 Rust",                true),
+        ("程序",          "今天编程 Rust",                false),
+        ("プログラミング", "Rustプログラミング",           true),
+        ("",             "anything",                    false),
+    ];
+
+    for (term, title, expected) in table {
+        let m = matcher(term, MatchMode::Word);
+        assert_eq!(m.is_match(title), expected, "{:?} in {:?}", term, title);
+    }
+}
+
+#[test]
+fn words_case_sensitive() {
+    let m = Matcher::new("Rust", MatchMode::Word, true).unwrap();
+    assert!(m.is_match("Rust gamedev"));
+    assert!(!m.is_match("rust gamedev"));
+}
+
+#[test]
+fn fuzzy() {
+    let fuzzy = |term| {
+        matcher(
+            term,
+            MatchMode::Fuzzy {
+                threshold: DEFAULT_THRESHOLD,
+            },
+        )
+    };
+
+    assert_eq!(fuzzy("rust").score("Rust and chill"), Some(1.0));
+    assert_eq!(fuzzy("rust").score("🦀 rsut 🦀"), Some(0.875));
+    assert_eq!(fuzzy("rust").score("ruts!!"), Some(0.875));
+    assert_eq!(fuzzy("bevy").score("bevvy jam"), Some(0.8));
+    assert_eq!(fuzzy("rust").score("python"), None);
+
+    // A different letter makes a different word, not a typo
+    assert_eq!(fuzzy("rust").score("just chatting"), None);
+    assert_eq!(fuzzy("rust").score("must see"), None);
+    assert_eq!(fuzzy("rust").score("rest"), None);
+
+    // Each word of the term counts equally
+    assert_eq!(fuzzy("rust bevy").score("rust bevvy"), Some(0.9));
+    assert_eq!(fuzzy("rust bevy").score("rust only"), None);
+
+    let strict = matcher("rust", MatchMode::Fuzzy { threshold: 0.9 });
+    assert_eq!(strict.score("rsut"), None);
+    let exact = Matcher::new("RUST", MatchMode::Fuzzy { threshold: 0.8 }, true).unwrap();
+    assert_eq!(exact.score("RUST stuff"), Some(1.0));
+    assert_eq!(exact.score("rust stuff"), None);
+
+    let loose = matcher("rust", MatchMode::Fuzzy { threshold: 0.75 });
+    assert_eq!(loose.score("just"), Some(0.75));
+}
+
+fn live(viewers: i64, uptime: Option<Duration>) -> Entry {
+    Entry {
+        viewer_count: viewers,
+        started_at: uptime.map(|uptime| Utc::now() - uptime),
+        ..common::entry("rust")
+    }
+}
+
+#[test]
+fn viewer_range() {
+    let filter = Filter {
+        min_viewers: Some(5),
+        max_viewers: Some(50),
+        ..Filter::default()
+    };
+
+    assert!(!filter.matches(&live(4, None)));
+    assert!(filter.matches(&live(5, None)));
+    assert!(filter.matches(&live(50, None)));
+    assert!(!filter.matches(&live(51, None)));
+}
+
+#[test]
+fn uptime_range() {
+    let filter = Filter {
+        min_uptime: Some(Duration::minutes(10)),
+        max_uptime: Some(Duration::hours(1)),
+        ..Filter::default()
+    };
+
+    assert!(!filter.matches(&live(1, Some(Duration::minutes(5)))));
+    assert!(filter.matches(&live(1, Some(Duration::minutes(30)))));
+    assert!(!filter.matches(&live(1, Some(Duration::hours(2)))));
+    assert!(!filter.matches(&live(1, None)));
+    assert!(Filter::default().matches(&live(1, None)));
+}
+
+#[test]
+fn durations() {
+    assert_eq!(parse_duration("45s"), Ok(Duration::seconds(45)));
+    assert_eq!(parse_duration("30m"), Ok(Duration::minutes(30)));
+    assert_eq!(parse_duration("2h"), Ok(Duration::hours(2)));
+    assert_eq!(parse_duration("1d"), Ok(Duration::days(1)));
+    assert_eq!(parse_duration("1h30m"), Ok(Duration::minutes(90)));
+
+    assert!(parse_duration("").is_err());
+    assert!(parse_duration("30").is_err());
+    assert!(parse_duration("m").is_err());
+    assert!(parse_duration("2w").is_err());
+    assert!(parse_duration("-5m").is_err());
+    assert!(parse_duration("9999999999999999d").is_err());
+    assert!(parse_duration("99999999999999999999s").is_err());
+    assert!(parse_duration("100000000000d100000000000d").is_err());
+}
+
+#[test]
+fn thresholds() {
+    assert_eq!(parse_threshold("0"), Ok(0.0));
+    assert_eq!(parse_threshold("0.75"), Ok(0.75));
+    assert_eq!(parse_threshold("1.0"), Ok(1.0));
+
+    assert!(parse_threshold("5").is_err());
+    assert!(parse_threshold("-1").is_err());
+    assert!(parse_threshold("NaN").is_err());
+    assert!(parse_threshold("high").is_err());
+}
+
+#[test]
+fn excluded_terms() {
+    let title = |title: &str| Entry {
+        title: title.to_string(),
+        ..live(1, None)
+    };
+    let filter = Filter {
+        excluded_terms: vec![
+            matcher("giveaway", MatchMode::Word),
+            matcher("24/7", MatchMode::Word),
+            matcher("rerun", MatchMode::Word),
+        ],
+        ..Filter::default()
+    };
+
+    assert!(filter.matches(&title("writing rust")));
+    assert!(!filter.matches(&title("rust + GIVEAWAY!")));
+    assert!(!filter.matches(&title("24/7 lofi rust")));
+    assert!(!filter.matches(&title("[Rerun] rust")));
+    assert!(filter.matches(&title("reruns of rust")));
+
+    let filter = Filter {
+        excluded_terms: vec![matcher(r"re-?run", MatchMode::Regex)],
+        ..Filter::default()
+    };
+    assert!(!filter.matches(&title("RE-RUN: rust")));
+}
+
+#[test]
+fn tags() {
+    let tagged = |tags: &[&str]| Entry {
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        ..live(1, None)
+    };
+    let filter = Filter {
+        tags: vec!["rust".to_string()],
+        any_tags: vec!["bevy".to_string(), "godot".to_string()],
+        excluded_tags: vec!["giveaway".to_string()],
+        ..Filter::default()
+    };
+
+    assert!(filter.matches(&tagged(&["Rust", "Bevy"])));
+    assert!(filter.matches(&tagged(&["English", "rust", "Godot"])));
+    assert!(!filter.matches(&tagged(&["Bevy"])));
+    assert!(!filter.matches(&tagged(&["Rust"])));
+    assert!(!filter.matches(&tagged(&["Rust", "Bevy", "Giveaway"])));
+    assert!(!filter.matches(&tagged(&[])));
+    assert!(Filter::default().matches(&tagged(&[])));
+}
+
+#[test]
+fn mature_and_labels() {
+    let stream = |is_mature, labels: &[&str]| Entry {
+        is_mature,
+        labels: labels.iter().map(|l| l.to_string()).collect(),
+        ..live(1, None)
+    };
+    let filter = Filter {
+        no_mature: true,
+        excluded_labels: vec!["gambling".to_string()],
+        ..Filter::default()
+    };
+
+    assert!(filter.matches(&stream(false, &[])));
+    assert!(filter.matches(&stream(false, &["ProfanityVulgarity"])));
+    assert!(!filter.matches(&stream(true, &[])));
+    assert!(!filter.matches(&stream(false, &["Gambling"])));
+    assert!(Filter::default().matches(&stream(true, &["Gambling"])));
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use chrono::{Duration, TimeZone, Utc};
+use serde_json::json;
+use twitch_search::json::{document, Stream, Terms};
+use twitch_search::{CategoryTotal, Entry, MatchMode, Query, SearchResult, Sort};
+
+#[test]
+fn streams_have_raw_numbers() {
+    let started_at = Utc.with_ymd_and_hms(2021, 3, 10, 15, 4, 21).unwrap();
+    let entry = Entry {
+        viewer_count: 1234,
+        started_at: Some(started_at),
+        ..common::entry("rust")
+    };
+
+    let value = serde_json::to_value(Stream::from(&entry)).unwrap();
+
+    assert_eq!(value["viewer_count"], json!(1234));
+    assert_eq!(value["started_at"], json!("2021-03-10T15:04:21Z"));
+    let uptime = (Utc::now() - started_at).num_seconds();
+    let reported = value["uptime_seconds"].as_i64().unwrap();
+    assert!((uptime - reported).abs() <= 1);
+    assert_eq!(value["login"], json!("alice"));
+
+    let unknown = serde_json::to_value(Stream::from(&common::entry("rust"))).unwrap();
+    assert_eq!(unknown["started_at"], json!(null));
+    assert_eq!(unknown["uptime_seconds"], json!(null));
+}
+
+#[test]
+fn document_has_query_crawl_and_streams() {
+    let started_at = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+    let mut query = Query {
+        sort: Sort::Viewers,
+        ..Query::default()
+    };
+    query.filter.min_uptime = Some(Duration::minutes(30));
+    let result = SearchResult {
+        entries: vec![common::entry("rust")],
+        total: 7,
+        categories: vec![CategoryTotal {
+            category: "1469308723".to_string(),
+            total: 7,
+            found: 1,
+        }],
+        pages: 2,
+        complete: true,
+    };
+    let terms = Terms {
+        term: Some("rust"),
+        mode: MatchMode::Word,
+        case_sensitive: false,
+        excluded_terms: &["giveaway".to_string()],
+    };
+
+    let document = document(terms, &query, &result, started_at);
+
+    assert_eq!(document["query"]["term"], json!("rust"));
+    assert_eq!(document["query"]["mode"], json!("word"));
+    assert_eq!(document["query"]["sort"], json!("viewers"));
+    assert_eq!(document["query"]["excluded_terms"], json!(["giveaway"]));
+    assert_eq!(document["query"]["min_uptime_seconds"], json!(1800));
+    assert_eq!(
+        document["crawl"]["started_at"],
+        json!("2024-01-02T03:04:05Z")
+    );
+    assert_eq!(document["crawl"]["pages"], json!(2));
+    assert_eq!(document["crawl"]["total"], json!(7));
+    assert_eq!(document["crawl"]["found"], json!(1));
+    assert_eq!(document["streams"][0]["title"], json!("rust"));
+}
+
+#[test]
+fn only_leaves_out_the_categories() {
+    let query = Query {
+        only: vec!["alice".to_string()],
+        ..Query::default()
+    };
+    let result = SearchResult {
+        entries: Vec::new(),
+        total: 0,
+        categories: Vec::new(),
+        pages: 1,
+        complete: true,
+    };
+    let terms = Terms {
+        term: None,
+        mode: MatchMode::Substring,
+        case_sensitive: false,
+        excluded_terms: &[],
+    };
+
+    let document = document(terms, &query, &result, Utc::now());
+
+    assert_eq!(document["query"]["categories"], json!([]));
+    assert_eq!(document["query"]["only"], json!(["alice"]));
+    assert_eq!(document["crawl"]["categories"], json!([]));
+}
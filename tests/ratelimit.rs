@@ -0,0 +1,102 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use chrono::Utc;
+use common::{page, stream, Recorded, Reply, Stub};
+use serde_json::json;
+use twitch_search::{search, Client, Error, Query, RetryPolicy};
+
+fn client(stub: &Stub) -> Client {
+    stub.client().with_retry_policy(RetryPolicy {
+        max_retries: 3,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(20),
+        min_remaining: 2,
+    })
+}
+
+/// Fail with `status` for the first `failures` requests, then succeed
+fn flaky(status: u16, failures: usize) -> impl Fn(&Recorded) -> Reply + Send + 'static {
+    let count = AtomicUsize::new(0);
+    move |_| {
+        if count.fetch_add(1, Ordering::SeqCst) < failures {
+            Reply::status(status, json!({ "status": status, "message": "nope" }))
+        } else {
+            Reply::json(page(vec![stream("alice", "rust", 1)], None))
+        }
+    }
+}
+
+#[test]
+fn retries_transient_errors() {
+    let stub = Stub::start(flaky(503, 2));
+    let client = client(&stub);
+
+    let result = search(&client, &Query::default()).unwrap();
+
+    assert_eq!(result.entries.len(), 1);
+    let stats = client.stats();
+    assert_eq!(stats.requests, 3);
+    assert_eq!(stats.retries.get(&503), Some(&2));
+}
+
+#[test]
+fn retries_rate_limited_requests() {
+    let stub = Stub::start(flaky(429, 1));
+    let client = client(&stub);
+
+    search(&client, &Query::default()).unwrap();
+
+    assert_eq!(client.stats().retries.get(&429), Some(&1));
+}
+
+#[test]
+fn gives_up_eventually() {
+    let stub = Stub::start(flaky(429, usize::MAX));
+    let client = client(&stub);
+
+    assert!(matches!(
+        search(&client, &Query::default()),
+        Err(Error::RateLimited(_))
+    ));
+    assert_eq!(client.stats().requests, 4);
+}
+
+#[test]
+fn does_not_retry_client_errors() {
+    let stub = Stub::start(flaky(400, 1));
+    let client = client(&stub);
+
+    assert!(matches!(
+        search(&client, &Query::default()),
+        Err(Error::Http { status: 400, .. })
+    ));
+    assert_eq!(client.stats().requests, 1);
+}
+
+#[test]
+fn pauses_when_the_bucket_is_nearly_empty() {
+    let count = AtomicUsize::new(0);
+    let stub = Stub::start(move |_| {
+        let n = count.fetch_add(1, Ordering::SeqCst);
+        let reset = (Utc::now().timestamp() + 30).to_string();
+        let cursor = format!("page-{}", n + 1);
+        let cursor = if n < 2 { Some(cursor.as_str()) } else { None };
+        Reply::json(page(vec![stream(&format!("s{}", n), "rust", 1)], cursor))
+            .header("Ratelimit-Limit", "800")
+            .header("Ratelimit-Remaining", if n == 0 { "1" } else { "700" })
+            .header("Ratelimit-Reset", &reset)
+    });
+    let client = client(&stub);
+
+    let result = search(&client, &Query::default()).unwrap();
+
+    assert_eq!(result.total, 3);
+    let stats = client.stats();
+    assert_eq!(stats.pauses, 1);
+    // Capped by max_delay rather than sleeping the whole 30s
+    assert!(stats.waited >= Duration::from_millis(20));
+    assert!(stats.waited < Duration::from_secs(1));
+}
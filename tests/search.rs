@@ -0,0 +1,432 @@
+mod common;
+
+use common::{page, paginated, stream, Reply, Stub};
+use serde_json::json;
+use twitch_search::transport::{Request, Response, Transport};
+use twitch_search::{search, search_each, Client, Error, Expr, Filter, MatchMode, Query, Sort};
+
+fn query(term: &str) -> Query {
+    Query {
+        filter: Filter {
+            query: Some(Expr::parse(term, MatchMode::Substring, false).unwrap()),
+            ..Filter::default()
+        },
+        ..Query::default()
+    }
+}
+
+#[test]
+fn crawls_every_page_and_filters() {
+    let stub = Stub::start(paginated(vec![
+        vec![
+            stream("alice", "writing rust", 10),
+            stream("bob", "painting", 3),
+        ],
+        vec![stream("carol", "more rust things", 7)],
+        vec![stream("dave", "cooking", 1)],
+    ]));
+
+    let result = search(&stub.client(), &query("rust")).unwrap();
+
+    let names = result
+        .entries
+        .iter()
+        .map(|e| e.display_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["alice", "carol"]);
+    assert_eq!(result.total, 4);
+    assert_eq!(result.pages, 3);
+
+    let requests = stub.requests_to("/helix/streams");
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].param("after"), None);
+    assert_eq!(requests[1].param("after"), Some("page-1"));
+    assert_eq!(requests[2].param("after"), Some("page-2"));
+}
+
+#[test]
+fn sends_credentials_and_category() {
+    let stub = Stub::start(paginated(vec![vec![]]));
+
+    search(&stub.client(), &Query::default()).unwrap();
+
+    let request = &stub.requests()[0];
+    assert_eq!(request.param("game_id"), Some("1469308723"));
+    assert_eq!(request.param("first"), Some("100"));
+    assert_eq!(request.headers["authorization"], "Bearer token");
+    assert_eq!(request.headers["client-id"], "client-id");
+}
+
+#[test]
+fn limit_keeps_counting_the_total() {
+    let stub = Stub::start(paginated(vec![
+        vec![stream("alice", "rust", 1), stream("bob", "rust", 1)],
+        vec![stream("carol", "rust", 1)],
+    ]));
+
+    let query = Query {
+        limit: 1,
+        ..query("rust")
+    };
+    let result = search(&stub.client(), &query).unwrap();
+
+    assert_eq!(result.entries.len(), 1);
+    assert_eq!(result.total, 3);
+}
+
+#[test]
+fn resolves_category_names() {
+    let stub = Stub::start(|request| match request.path.as_str() {
+        "/helix/games" => {
+            assert_eq!(request.param("name"), Some("Software and Game Development"));
+            Reply::json(page(vec![json!({ "id": "1469308723", "name": "x" })], None))
+        }
+        _ => Reply::json(page(vec![stream("alice", "rust", 1)], None)),
+    });
+
+    let query = Query {
+        categories: vec!["Software and Game Development".to_string()],
+        ..query("rust")
+    };
+    let result = search(&stub.client(), &query).unwrap();
+
+    assert_eq!(result.entries.len(), 1);
+    assert_eq!(
+        stub.requests_to("/helix/streams")[0].param("game_id"),
+        Some("1469308723")
+    );
+}
+
+#[test]
+fn unknown_category() {
+    let stub = Stub::start(|_| Reply::json(page(vec![], None)));
+
+    let query = Query {
+        categories: vec!["Nope".to_string()],
+        ..Query::default()
+    };
+
+    match search(&stub.client(), &query) {
+        Err(Error::UnknownCategory(name)) => assert_eq!(name, "Nope"),
+        other => panic!(
+            "expected unknown category, got {:?}",
+            other.map(|r| r.total)
+        ),
+    }
+}
+
+#[test]
+fn merges_categories_without_duplicates() {
+    let stub = Stub::start(|request| match request.param("game_id") {
+        Some("1") => Reply::json(page(
+            vec![stream("alice", "rust", 1), stream("bob", "go", 1)],
+            None,
+        )),
+        _ => Reply::json(page(
+            vec![stream("alice", "rust", 1), stream("carol", "rust", 1)],
+            None,
+        )),
+    });
+
+    let query = Query {
+        categories: vec!["1".to_string(), "2".to_string()],
+        ..query("rust")
+    };
+    let result = search(&stub.client(), &query).unwrap();
+
+    assert_eq!(result.entries.len(), 2);
+    assert_eq!(result.total, 4);
+    assert_eq!(result.categories[0].found, 1);
+    assert_eq!(result.categories[0].total, 2);
+    assert_eq!(result.categories[1].found, 1);
+    assert_eq!(result.categories[1].total, 2);
+}
+
+#[test]
+fn tolerates_nulls_and_missing_fields() {
+    let stub = Stub::start(|_| {
+        Reply::json(json!({
+            "data": [{ "user_id": "1", "user_name": "alice", "title": "rust", "language": null }],
+            "pagination": null,
+        }))
+    });
+
+    let result = search(&stub.client(), &query("rust")).unwrap();
+
+    assert_eq!(result.entries[0].lang, "");
+    assert_eq!(result.entries[0].started_at, None);
+}
+
+#[test]
+fn unauthorized() {
+    let stub = Stub::start(|_| {
+        Reply::status(
+            401,
+            json!({ "error": "Unauthorized", "status": 401, "message": "Invalid OAuth token" }),
+        )
+    });
+
+    match search(&stub.client(), &Query::default()) {
+        Err(e @ Error::Unauthorized(_)) => {
+            assert_eq!(e.exit_code(), 4);
+            assert!(e.to_string().contains("Invalid OAuth token"));
+        }
+        other => panic!("expected unauthorized, got {:?}", other.map(|r| r.total)),
+    }
+}
+
+#[test]
+fn missing_data() {
+    let stub = Stub::start(|_| Reply::json(json!({ "pagination": {} })));
+
+    assert!(matches!(
+        search(&stub.client(), &Query::default()),
+        Err(Error::MissingField("data"))
+    ));
+}
+
+#[test]
+fn client_can_move_between_threads() {
+    fn assert_send<T: Send>() {}
+    assert_send::<Client>();
+}
+
+#[test]
+fn custom_transport() {
+    struct Canned;
+
+    impl Transport for Canned {
+        fn send(&self, request: &Request) -> twitch_search::Result<Response> {
+            assert_eq!(request.url.host_str(), Some("api.twitch.tv"));
+            Ok(Response {
+                status: 200,
+                body: page(vec![stream("alice", "rust", 1)], None).to_string(),
+                ..Response::default()
+            })
+        }
+    }
+
+    let client = Client::new("client-id", "token").with_transport(Canned);
+    let result = search(&client, &query("rust")).unwrap();
+
+    assert_eq!(result.entries.len(), 1);
+}
+
+#[test]
+fn fast_search_stops_at_the_limit() {
+    let stub = Stub::start(paginated(vec![
+        vec![stream("alice", "rust", 1), stream("bob", "go", 1)],
+        vec![stream("carol", "rust", 1)],
+        vec![stream("dave", "rust", 1)],
+    ]));
+
+    let query = Query {
+        limit: 1,
+        fast: true,
+        ..query("rust")
+    };
+    let result = search(&stub.client(), &query).unwrap();
+
+    assert_eq!(result.entries.len(), 1);
+    assert_eq!(result.total, 2);
+    assert!(!result.complete);
+    assert_eq!(result.pages, 1);
+    assert_eq!(stub.requests_to("/helix/streams").len(), 1);
+}
+
+#[test]
+fn fast_search_without_enough_matches_is_complete() {
+    let stub = Stub::start(paginated(vec![
+        vec![stream("alice", "rust", 1)],
+        vec![stream("bob", "go", 1)],
+    ]));
+
+    let query = Query {
+        limit: 5,
+        fast: true,
+        ..query("rust")
+    };
+    let result = search(&stub.client(), &query).unwrap();
+
+    assert_eq!(result.entries.len(), 1);
+    assert_eq!(result.total, 2);
+    assert!(result.complete);
+}
+
+#[test]
+fn fuzzy_matches_are_sorted_by_score() {
+    let stub = Stub::start(paginated(vec![vec![
+        stream("alice", "rsut", 1),
+        stream("bob", "golang", 1),
+        stream("carol", "Rust!", 1),
+        stream("dave", "bevvy and rustt", 1),
+    ]]));
+
+    let query = Query {
+        filter: Filter {
+            query: Some(Expr::parse("rust", MatchMode::Fuzzy { threshold: 0.75 }, false).unwrap()),
+            ..Filter::default()
+        },
+        limit: 2,
+        ..Query::default()
+    };
+    let result = search(&stub.client(), &query).unwrap();
+
+    let found = result
+        .entries
+        .iter()
+        .map(|e| (e.display_name.as_str(), e.score))
+        .collect::<Vec<_>>();
+    assert_eq!(found, [("carol", 1.0), ("alice", 0.875)]);
+}
+
+#[test]
+fn languages_are_sent_and_checked() {
+    // The stub ignores the language parameters, the filter still applies
+    let stub = Stub::start(|_| {
+        let mut german = stream("bob", "rust", 1);
+        german["language"] = json!("de");
+        let mut french = stream("carol", "rust", 1);
+        french["language"] = json!("fr");
+        Reply::json(page(vec![stream("alice", "rust", 1), german, french], None))
+    });
+
+    let query = Query {
+        filter: Filter {
+            languages: vec!["en".to_string(), "de".to_string()],
+            ..Filter::default()
+        },
+        ..Query::default()
+    };
+    let result = search(&stub.client(), &query).unwrap();
+
+    let names = result
+        .entries
+        .iter()
+        .map(|e| e.display_name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["alice", "bob"]);
+    assert_eq!(stub.requests()[0].params("language"), ["en", "de"]);
+}
+
+#[test]
+fn sorts_before_the_limit() {
+    let stub = Stub::start(paginated(vec![
+        vec![stream("carol", "rust", 5), stream("alice", "rust", 50)],
+        vec![stream("Bob", "rust", 20), stream("dave", "rust", 1)],
+    ]));
+
+    let names = |sort: Sort, reverse: bool| {
+        let query = Query {
+            sort,
+            reverse,
+            limit: 2,
+            ..Query::default()
+        };
+        search(&stub.client(), &query)
+            .unwrap()
+            .entries
+            .into_iter()
+            .map(|e| e.display_name)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(names(Sort::Relevance, false), ["carol", "alice"]);
+    assert_eq!(names(Sort::Relevance, true), ["dave", "Bob"]);
+    assert_eq!(names(Sort::Viewers, false), ["alice", "Bob"]);
+    assert_eq!(names(Sort::Viewers, true), ["dave", "carol"]);
+    assert_eq!(names(Sort::Name, false), ["alice", "Bob"]);
+    assert_eq!(names(Sort::Name, true), ["dave", "carol"]);
+}
+
+#[test]
+fn sorts_by_uptime() {
+    let stub = Stub::start(|_| {
+        let mut early = stream("early", "rust", 1);
+        early["started_at"] = json!("2021-03-10T10:00:00Z");
+        let mut late = stream("late", "rust", 1);
+        late["started_at"] = json!("2021-03-10T12:00:00Z");
+        let mut unknown = stream("unknown", "rust", 1);
+        unknown["started_at"] = json!(null);
+        Reply::json(page(vec![late, unknown, early], None))
+    });
+
+    let query = Query {
+        sort: Sort::Uptime,
+        ..Query::default()
+    };
+    let names = search(&stub.client(), &query)
+        .unwrap()
+        .entries
+        .into_iter()
+        .map(|e| e.display_name)
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, ["early", "late", "unknown"]);
+}
+
+#[test]
+fn only_looks_up_logins_in_batches() {
+    let stub = Stub::start(|request| {
+        let streams = request
+            .params("user_login")
+            .into_iter()
+            .filter(|login| login.ends_with('7'))
+            .map(|login| stream(login, "anything", 1))
+            .collect();
+        Reply::json(page(streams, None))
+    });
+
+    let query = Query {
+        only: (0..150).map(|n| format!("user{}", n)).collect(),
+        ..Query::default()
+    };
+    let result = search(&stub.client(), &query).unwrap();
+
+    let requests = stub.requests_to("/helix/streams");
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].params("user_login").len(), 100);
+    assert_eq!(requests[1].params("user_login").len(), 50);
+    assert_eq!(requests[0].param("game_id"), None);
+    assert_eq!(result.entries.len(), 15);
+    assert_eq!(result.total, 15);
+    assert!(result.categories.is_empty());
+}
+
+#[test]
+fn search_each_hands_over_matches_per_page() {
+    let stub = Stub::start(paginated(vec![
+        vec![stream("alice", "rust", 1), stream("bob", "go", 1)],
+        vec![stream("carol", "rust", 1)],
+        vec![stream("dave", "rust", 1)],
+    ]));
+
+    let mut seen = Vec::new();
+    let query = Query {
+        limit: 2,
+        ..query("rust")
+    };
+    let result = search_each(&stub.client(), &query, |entry| {
+        let requests = stub.requests_to("/helix/streams").len();
+        seen.push((entry.display_name.clone(), requests));
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(seen, [("alice".to_string(), 1), ("carol".to_string(), 2)]);
+    assert_eq!(result.entries.len(), 2);
+    assert_eq!(result.total, 4);
+}
+
+#[test]
+fn search_each_stops_on_error() {
+    let stub = Stub::start(paginated(vec![
+        vec![stream("alice", "rust", 1)],
+        vec![stream("bob", "rust", 1)],
+    ]));
+
+    let result = search_each(&stub.client(), &query("rust"), |_| Err(Error::BrokenPipe));
+
+    assert!(matches!(result, Err(Error::BrokenPipe)));
+    assert_eq!(stub.requests_to("/helix/streams").len(), 1);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use twitch_search::{popular_tags, Entry, TagCount};
+
+fn entry(viewers: i64, tags: &[&str]) -> Entry {
+    Entry {
+        viewer_count: viewers,
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        ..common::entry("rust")
+    }
+}
+
+#[test]
+fn ranks_by_streams_then_viewers() {
+    let entries = [
+        entry(10, &["Rust", "English"]),
+        entry(3, &["rust", "Bevy", "RUST"]),
+        entry(20, &["English"]),
+        entry(7, &["Godot"]),
+        entry(7, &["bevy"]),
+    ];
+
+    let count = |tag: &str, streams, viewers| TagCount {
+        tag: tag.to_string(),
+        streams,
+        viewers,
+    };
+    assert_eq!(
+        popular_tags(&entries),
+        [
+            count("English", 2, 30),
+            count("Rust", 2, 13),
+            count("Bevy", 2, 10),
+            count("Godot", 1, 7),
+        ]
+    );
+    assert!(popular_tags(&[]).is_empty());
+}